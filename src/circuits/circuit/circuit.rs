@@ -1,14 +1,360 @@
+use crate::circuits::deserializer::from_json;
 use crate::circuits::errors::CircuitError;
 use crate::circuits::indexed_map::IndexedMap;
-use serde::{Deserialize, Serialize};
+use crate::circuits::loader::Loader;
+use crate::gadgets::boolean::{
+    add_words, and_bits, from_bits, shift_left, shift_right, to_bits, xor_bits,
+};
+use crate::gadgets::{
+    babyjubjub::PointSignal, ecdsa_verify, eddsa_verify, fixed_mul, less_than, lookup,
+    merkle_membership, mimc7, mimc_feistel, poseidon, sha256, ConstraintSystem, CostCounter,
+    Signal,
+};
+use crate::utils::field::field::{bit_length, FieldElement};
+use crate::utils::fixed::fixed::FixedPoint;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::fs;
 use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Type of a circuit value. Integer types and `bool` are enforced with range and booleanity
+/// constraints, `field` is unconstrained.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Type {
+    Bool,
+    U8,
+    U32,
+    U64,
+    Field,
+}
+
+impl Type {
+    /// Bit width of the type, `None` for `field`.
+    pub fn bits(&self) -> Option<usize> {
+        match self {
+            Type::Bool => Some(1),
+            Type::U8 => Some(8),
+            Type::U32 => Some(32),
+            Type::U64 => Some(64),
+            Type::Field => None,
+        }
+    }
+
+    /// Bit width of an integer type, panicking for `field`.
+    fn width(&self) -> usize {
+        self.bits()
+            .unwrap_or_else(|| panic!("Bitwise operations need an integer type, found {:?}", self))
+    }
+
+    pub fn contains(&self, value: &FieldElement) -> bool {
+        self.bits().is_none_or(|bits| bit_length(value) <= bits)
+    }
+
+    /// Constrains `value` to the type: booleanity for `bool`, a bit decomposition for the
+    /// integer types.
+    pub fn enforce<CS: ConstraintSystem>(&self, cs: &mut CS, value: &Signal) {
+        match self {
+            Type::Bool => cs.assert_boolean(value),
+            Type::Field => {}
+            _ => {
+                to_bits(cs, value, self.width());
+            }
+        }
+    }
+
+    /// Number of auxiliary variables `enforce` allocates.
+    pub fn auxiliary_count(&self) -> usize {
+        let mut counter = CostCounter::default();
+        self.enforce(&mut counter, &Signal::default());
+        counter.variables
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash)]
 pub enum Operation {
     Add,
     Multiply,
+    /// Decomposes the operand into `n` little-endian boolean witness variables.
+    ToBits {
+        n: usize,
+    },
+    /// Constrains the operand to lie in `[0, 2^bits)`.
+    RangeCheck {
+        bits: usize,
+    },
+    /// `Select(cond, a, b)` evaluates to `a` when the boolean `cond` is 1 and to `b` when it is 0.
+    Select,
+    /// Raises the operand to a constant power using square-and-multiply.
+    Pow {
+        exponent: u64,
+    },
+    /// Poseidon hash of 2 to 16 operands.
+    Poseidon,
+    /// MiMC-7 encryption of the first operand under the key given as second operand.
+    MiMC {
+        rounds: usize,
+    },
+    /// Left half of the MiMC Feistel permutation of `(left, right)` under the key given as
+    /// third operand.
+    MiMCFeistel {
+        rounds: usize,
+    },
+    /// The `output`-th 128-bit half of the big-endian SHA-256 digest of the operands taken as
+    /// message bytes, 0 for the most significant half. Each half hashes the message again.
+    Sha256 {
+        #[serde(default)]
+        output: usize,
+    },
+    /// Proves that a leaf belongs to the tree with the given root. Operands are the leaf, the
+    /// root, the `depth` sibling hashes from the leaf level up, then the `depth` direction bits
+    /// (1 when the node is a right child). Nodes are hashed with the two-input `hash`
+    /// operation and the recomputed root is the output.
+    MerkleMembership {
+        depth: usize,
+        #[serde(deserialize_with = "merkle_hash")]
+        hash: Box<Operation>,
+    },
+    /// Verifies an EdDSA-Poseidon signature over Baby Jubjub, as circomlib's
+    /// `EdDSAPoseidonVerifier` does. Operands are the public key `(Ax, Ay)`, the signature
+    /// `(R8x, R8y, S)` and the signed message. Evaluates to 1 and is only satisfiable when the
+    /// signature is valid.
+    EdDSAVerify,
+    /// Verifies a secp256k1 ECDSA signature. Operands are the little-endian 64-bit limbs of the
+    /// prehashed message, of the signature's `r` and `s`, and of the public key's `x` and `y`,
+    /// four limbs each. Evaluates to 1 and is only satisfiable when the signature is valid.
+    ECDSAVerify,
+    /// Product of two fixed-point operands with `scale` fractional bits, rounded down. Fixed-point
+    /// operands of equal scale are added with `Add`.
+    FixedMul {
+        scale: usize,
+    },
+    /// 1 when the first operand is less than the second and 0 otherwise, reading both as signed
+    /// integers of `FIXED_POINT_BITS` bits, such as fixed-point values of equal scale. Operands
+    /// out of that range make the circuit unsatisfiable.
+    LessThan,
+    /// Sum of two integers of the given type, wrapping around on overflow.
+    WrappingAdd {
+        #[serde(rename = "type")]
+        ty: Type,
+    },
+    /// Bitwise XOR of two integers of the given type.
+    Xor {
+        #[serde(rename = "type")]
+        ty: Type,
+    },
+    /// Bitwise AND of two integers of the given type.
+    And {
+        #[serde(rename = "type")]
+        ty: Type,
+    },
+    /// Shifts an integer of the given type towards its most significant end, dropping the bits
+    /// shifted out.
+    ShiftLeft {
+        #[serde(rename = "type")]
+        ty: Type,
+        amount: usize,
+    },
+    /// Shifts an integer of the given type towards its least significant end.
+    ShiftRight {
+        #[serde(rename = "type")]
+        ty: Type,
+        amount: usize,
+    },
+    /// Sum of the operands, typically the elements of an array.
+    Sum,
+    /// Dot product of the first and second halves of the operands, such as `Dot(v, w)` with
+    /// arrays of equal length.
+    Dot,
+    /// Applies `operation` element-wise to arrays of equal length, broadcasting scalar
+    /// operands. Only valid as an operand, where it expands into one circuit per element.
+    Map {
+        operation: Box<Operation>,
+    },
+    /// The `output`-th value computed from the operands by the hint registered as `name`, such
+    /// as `inverse`, `sqrt`, `bits` or `divmod`. The value is not constrained: the circuit must
+    /// check it with other operations, typically `AssertEqual`.
+    Hint {
+        name: String,
+        #[serde(default)]
+        output: usize,
+    },
+    /// Enforces that both operands are equal and evaluates to the first one.
+    AssertEqual,
+    /// Proves that the operands are the first columns of a row of `table`. Rows one column
+    /// longer than the operands map them to their last column, which is the output, as in a
+    /// function table; otherwise the operation only proves membership and evaluates to 1.
+    Lookup {
+        table: Vec<Vec<i64>>,
+    },
+}
+
+impl Operation {
+    /// Numbers of operands the operation accepts.
+    pub fn arity(&self) -> RangeInclusive<usize> {
+        match self {
+            Operation::Add | Operation::Multiply | Operation::AssertEqual => 2..=2,
+            Operation::FixedMul { .. } | Operation::LessThan => 2..=2,
+            Operation::WrappingAdd { .. } | Operation::Xor { .. } | Operation::And { .. } => 2..=2,
+            Operation::ShiftLeft { .. } | Operation::ShiftRight { .. } => 1..=1,
+            Operation::ToBits { .. } | Operation::RangeCheck { .. } | Operation::Pow { .. } => {
+                1..=1
+            }
+            Operation::MiMC { .. } => 2..=2,
+            Operation::Select | Operation::MiMCFeistel { .. } => 3..=3,
+            Operation::EdDSAVerify => 6..=6,
+            Operation::ECDSAVerify => 20..=20,
+            Operation::Poseidon => 2..=16,
+            Operation::Sha256 { .. } => 1..=usize::MAX,
+            Operation::MerkleMembership { depth, .. } => 2 + 2 * depth..=2 + 2 * depth,
+            Operation::Sum | Operation::Map { .. } | Operation::Hint { .. } => 1..=usize::MAX,
+            Operation::Dot => 2..=usize::MAX,
+            Operation::Lookup { table } => {
+                let columns = table.first().map_or(1, Vec::len);
+                columns.saturating_sub(1).max(1)..=columns
+            }
+        }
+    }
+
+    /// Number of auxiliary witness variables the operation allocates besides its output.
+    pub fn auxiliary_count(&self, operand_count: usize) -> usize {
+        match self {
+            Operation::Add | Operation::Multiply | Operation::Select | Operation::Hint { .. } => 0,
+            Operation::ToBits { n } => *n,
+            Operation::RangeCheck { bits } => *bits,
+            // The last step of the chain writes directly to the output.
+            Operation::Pow { exponent } => {
+                square_and_multiply_chain(*exponent).len().saturating_sub(1)
+            }
+            _ => self
+                .gadget_cost(operand_count)
+                .map_or(0, |cost| cost.variables),
+        }
+    }
+
+    /// Variables and constraints of the gadget implementing the operation, not counting the
+    /// constraint that binds its result to the output variable.
+    pub fn gadget_cost(&self, operand_count: usize) -> Option<CostCounter> {
+        let mut counter = CostCounter::default();
+        self.synthesize(&mut counter, &vec![Signal::default(); operand_count])?;
+        Some(counter)
+    }
+
+    /// Runs the gadget implementing the operation, returning its output. Operations lowered
+    /// directly by the R1CS return `None`.
+    pub fn synthesize<CS: ConstraintSystem>(
+        &self,
+        cs: &mut CS,
+        inputs: &[Signal],
+    ) -> Option<Signal> {
+        match self {
+            Operation::Poseidon => Some(poseidon(cs, inputs)),
+            Operation::MiMC { rounds } => Some(mimc7(cs, &inputs[0], &inputs[1], *rounds)),
+            Operation::MiMCFeistel { rounds } => {
+                let (left, _) = mimc_feistel(cs, &inputs[0], &inputs[1], &inputs[2], *rounds);
+                Some(left)
+            }
+            Operation::Sha256 { output } => Some(sha256(cs, inputs)[*output].clone()),
+            Operation::MerkleMembership { depth, hash } => {
+                let (path, directions) = inputs[2..].split_at(*depth);
+                merkle_membership(cs, hash, &inputs[0], &inputs[1], path, directions)
+            }
+            Operation::EdDSAVerify => {
+                let point = |x: &Signal, y: &Signal| PointSignal {
+                    x: x.clone(),
+                    y: y.clone(),
+                };
+                let public_key = point(&inputs[0], &inputs[1]);
+                let r8 = point(&inputs[2], &inputs[3]);
+                eddsa_verify(cs, &public_key, &r8, &inputs[4], &inputs[5]);
+                Some(Signal::one())
+            }
+            Operation::ECDSAVerify => {
+                let limbs: Vec<&[Signal]> = inputs.chunks(4).collect();
+                ecdsa_verify(cs, limbs[0], limbs[1], limbs[2], (limbs[3], limbs[4]));
+                Some(Signal::one())
+            }
+            Operation::FixedMul { scale } => Some(fixed_mul(cs, &inputs[0], &inputs[1], *scale)),
+            Operation::LessThan => Some(less_than(cs, &inputs[0], &inputs[1])),
+            // Decomposing the operands range checks them against the type.
+            Operation::WrappingAdd { ty } => {
+                let a = to_bits(cs, &inputs[0], ty.width());
+                let b = to_bits(cs, &inputs[1], ty.width());
+                Some(from_bits(&add_words(cs, &[&a, &b])))
+            }
+            Operation::Xor { ty } => {
+                let a = to_bits(cs, &inputs[0], ty.width());
+                let b = to_bits(cs, &inputs[1], ty.width());
+                Some(from_bits(&xor_bits(cs, &a, &b)))
+            }
+            Operation::And { ty } => {
+                let a = to_bits(cs, &inputs[0], ty.width());
+                let b = to_bits(cs, &inputs[1], ty.width());
+                Some(from_bits(&and_bits(cs, &a, &b)))
+            }
+            Operation::ShiftLeft { ty, amount } => {
+                let bits = to_bits(cs, &inputs[0], ty.width());
+                Some(from_bits(&shift_left(&bits, *amount)))
+            }
+            Operation::ShiftRight { ty, amount } => {
+                let bits = to_bits(cs, &inputs[0], ty.width());
+                Some(from_bits(&shift_right(&bits, *amount)))
+            }
+            Operation::Sum => Some(inputs.iter().fold(Signal::zero(), |sum, x| &sum + x)),
+            Operation::Dot => {
+                let (a, b) = inputs.split_at(inputs.len() / 2);
+                let products: Vec<Signal> =
+                    a.iter().zip(b).map(|(x, y)| cs.multiply(x, y)).collect();
+                Some(products.iter().fold(Signal::zero(), |sum, x| &sum + x))
+            }
+            Operation::AssertEqual => {
+                cs.assert_equal(&inputs[0], &inputs[1]);
+                Some(inputs[0].clone())
+            }
+            Operation::Lookup { table } => {
+                let table: Vec<Vec<FieldElement>> = table
+                    .iter()
+                    .map(|row| row.iter().map(|&x| FieldElement::from(x)).collect())
+                    .collect();
+                Some(lookup(cs, &table, inputs))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reads the hash operation of `MerkleMembership`, which must be a gadget taking two operands.
+fn merkle_hash<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<Operation>, D::Error> {
+    let hash = Operation::deserialize(deserializer)?;
+    if !hash.arity().contains(&2) || hash.gadget_cost(2).is_none() {
+        return Err(D::Error::custom(format!(
+            "{:?} cannot hash Merkle tree nodes",
+            hash
+        )));
+    }
+    Ok(Box::new(hash))
+}
+
+/// Steps that raise a value to `exponent`, scanning its bits from the most significant one:
+/// `false` squares the accumulator and `true` multiplies it by the base.
+pub fn square_and_multiply_chain(exponent: u64) -> Vec<bool> {
+    if exponent < 2 {
+        return Vec::new();
+    }
+
+    let bit_length = 64 - exponent.leading_zeros();
+    let mut chain = Vec::new();
+    for i in (0..bit_length - 1).rev() {
+        chain.push(false);
+        if (exponent >> i) & 1 == 1 {
+            chain.push(true);
+        }
+    }
+    chain
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,6 +362,7 @@ pub enum Operation {
 pub enum Operand {
     Number(i64),
     Variable(String),
+    Fixed(FixedPoint),
     NestedCircuit(Box<Circuit>),
 }
 
@@ -25,6 +372,21 @@ pub struct Circuit {
     pub operands: Vec<Operand>,
     #[serde(default)]
     pub hash: u64,
+    /// Input variables revealed to the verifier. Only read on the root circuit, where they are
+    /// indexed right after the constant `1`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_inputs: Vec<String>,
+    /// Declared types of input variables, enforced when the witness is computed and by
+    /// constraints. Only read on the root circuit.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<String, Type>,
+    /// Type of the circuit's output, constrained after the operation.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<Type>,
+    /// Lengths of array inputs, whose elements are referenced as `v[i]` and which expand into
+    /// all their elements when named as operands. Only read on the root circuit.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub arrays: BTreeMap<String, usize>,
 }
 
 impl Circuit {
@@ -38,14 +400,32 @@ impl Circuit {
             operation,
             operands: vec![operand1, operand2],
             hash: hash.unwrap_or(0),
+            public_inputs: Vec::new(),
+            types: BTreeMap::new(),
+            ty: None,
+            arrays: BTreeMap::new(),
         }
     }
 
+    /// Reads a circuit file. Files using templates or loops are instantiated by a `Loader`,
+    /// others are streamed with `Circuit::from_json` and can be nested arbitrarily deep.
     pub fn from_file(file_path: &str) -> Result<Self, CircuitError> {
         let json = fs::read_to_string(file_path)
             .map_err(|e| CircuitError::FileReadError(e.to_string()))?;
 
-        serde_json::from_str(&json).map_err(|e| CircuitError::DeserializationError(e.to_string()))
+        match Circuit::from_json(&json) {
+            Err(CircuitError::ImportError(_)) => {
+                let json = Loader::new().load(Path::new(file_path))?;
+                serde_json::from_value(json)
+                    .map_err(|e| CircuitError::DeserializationError(e.to_string()))
+            }
+            circuit => circuit,
+        }
+    }
+
+    /// Deserializes a circuit without templates or loops, whatever its depth.
+    pub fn from_json(json: &str) -> Result<Self, CircuitError> {
+        from_json(json)
     }
 
     pub fn set_hash(&mut self, new_hash: u64) {
@@ -57,17 +437,157 @@ impl Circuit {
     }
 
     pub fn is_valid(&self) -> bool {
-        self.operands.len() == 2
+        let operand_count = self.operands.len();
+        match self.operation {
+            Operation::Dot => operand_count >= 2 && operand_count.is_multiple_of(2),
+            Operation::Sha256 { output } => output < 2 && operand_count >= 1,
+            _ => self.operation.arity().contains(&operand_count),
+        }
+    }
+
+    /// Name of the `index`-th element of an array.
+    pub fn element(array: &str, index: usize) -> String {
+        format!("{}[{}]", array, index)
+    }
+
+    /// Replaces array names by their elements and `Map` circuits by one circuit per element,
+    /// in operands, public inputs and declared types.
+    fn expand_arrays(&mut self) {
+        if let Operation::Map { .. } = self.operation {
+            panic!("Map produces an array and must be an operand of another operation");
+        }
+        let arrays = std::mem::take(&mut self.arrays);
+
+        let expand_name = |name: &String| -> Vec<String> {
+            match arrays.get(name) {
+                Some(&length) => (0..length).map(|i| Circuit::element(name, i)).collect(),
+                None => vec![name.clone()],
+            }
+        };
+        self.public_inputs = self.public_inputs.iter().flat_map(expand_name).collect();
+        self.types = self
+            .types
+            .iter()
+            .flat_map(|(name, &ty)| expand_name(name).into_iter().map(move |name| (name, ty)))
+            .collect();
+        self.expand_operands(&arrays);
+        self.arrays = arrays;
+    }
+
+    /// Expands the operands of the circuit and of all its nested circuits.
+    fn expand_operands(&mut self, arrays: &BTreeMap<String, usize>) {
+        let mut stack = vec![self];
+        while let Some(circuit) = stack.pop() {
+            let operands = std::mem::take(&mut circuit.operands);
+            circuit.operands = operands
+                .into_iter()
+                .flat_map(|operand| Circuit::expand_operand(operand, arrays))
+                .collect();
+            for operand in circuit.operands.iter_mut() {
+                if let Operand::NestedCircuit(nested) = operand {
+                    stack.push(nested);
+                }
+            }
+        }
+    }
+
+    /// Scalar operands an operand stands for. Nested circuits are returned as they are, their
+    /// own operands being expanded separately.
+    fn expand_operand(operand: Operand, arrays: &BTreeMap<String, usize>) -> Vec<Operand> {
+        match operand {
+            Operand::Variable(name) => {
+                if let Some(&length) = arrays.get(&name) {
+                    return (0..length)
+                        .map(|i| Operand::Variable(Circuit::element(&name, i)))
+                        .collect();
+                }
+                if let Some((array, index)) = name.strip_suffix(']').and_then(|n| n.split_once('['))
+                {
+                    if let (Some(&length), Ok(index)) = (arrays.get(array), index.parse::<usize>())
+                    {
+                        if index >= length {
+                            panic!(
+                                "Index {} is out of bounds for array {} of length {}",
+                                index, array, length
+                            );
+                        }
+                    }
+                }
+                vec![Operand::Variable(name)]
+            }
+            Operand::NestedCircuit(mut nested) => match nested.operation.clone() {
+                Operation::Map { operation } => {
+                    let columns: Vec<Vec<Operand>> = std::mem::take(&mut nested.operands)
+                        .into_iter()
+                        .map(|operand| Circuit::expand_operand(operand, arrays))
+                        .collect();
+                    let length = columns.iter().map(Vec::len).max().unwrap_or(0);
+                    if columns
+                        .iter()
+                        .any(|column| column.len() != length && column.len() != 1)
+                    {
+                        panic!("Map operands must be arrays of equal length or scalars");
+                    }
+
+                    (0..length)
+                        .map(|i| {
+                            let operands = columns
+                                .iter()
+                                .map(|column| column[i.min(column.len() - 1)].clone())
+                                .collect();
+                            Operand::NestedCircuit(Box::new(Circuit {
+                                operation: (*operation).clone(),
+                                operands,
+                                hash: 0,
+                                public_inputs: Vec::new(),
+                                types: BTreeMap::new(),
+                                ty: nested.ty,
+                                arrays: BTreeMap::new(),
+                            }))
+                        })
+                        .collect()
+                }
+                _ => vec![Operand::NestedCircuit(nested)],
+            },
+            operand => vec![operand],
+        }
+    }
+
+    /// Name of the `index`-th auxiliary variable allocated by the circuit with the given hash.
+    pub fn auxiliary_variable(hash: u64, index: usize) -> String {
+        format!("{}_{}", hash, index)
+    }
+
+    /// Name of the `index`-th auxiliary variable enforcing the declared type of an input.
+    pub fn type_variable(input: &str, index: usize) -> String {
+        format!("{}#{}", input, index)
+    }
+
+    /// Number of auxiliary variables the operation and the output type allocate.
+    pub fn auxiliary_count(&self) -> usize {
+        self.operation.auxiliary_count(self.operands.len())
+            + self.ty.map_or(0, |ty| ty.auxiliary_count())
     }
 
     /// Hashes and indexes the circuit, combining static and linearization variables.
     pub fn hash_and_index_circuit(&mut self) -> IndexedMap<String> {
+        self.expand_arrays();
+
         // Initialize linearization and static variables
         let mut linearization_variables = IndexedMap::new();
         let mut static_variables = IndexedMap::from_vector(vec!["1".to_string()]);
+        for public_input in &self.public_inputs {
+            static_variables.add(public_input.clone());
+        }
 
-        // Recursive hashing and indexing
-        self.hash_and_index_recursive(true, &mut static_variables, &mut linearization_variables);
+        // Hashing and indexing
+        self.hash_and_index_nodes(&mut static_variables, &mut linearization_variables);
+        for (input, ty) in &self.types {
+            static_variables.add(input.clone());
+            for index in 0..ty.auxiliary_count() {
+                linearization_variables.add(Circuit::type_variable(input, index));
+            }
+        }
 
         // Merge static and linearization variables into one IndexedMap
         let mut combined_variables = static_variables.into_vector();
@@ -75,43 +595,93 @@ impl Circuit {
         IndexedMap::from_vector(combined_variables)
     }
 
-    fn hash_and_index_recursive(
+    /// Hashes every circuit from its operands, indexing variables in the order a depth-first
+    /// traversal meets them. The traversal keeps an explicit stack, as circuits can be nested
+    /// deeper than recursion allows.
+    fn hash_and_index_nodes(
         &mut self,
-        root: bool,
         static_variables: &mut IndexedMap<String>,
         linearization_variables: &mut IndexedMap<String>,
-    ) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.operation.hash(&mut hasher);
+    ) {
+        // Hashes of the circuits in pre-order, assigned once all are known.
+        let mut hashes = vec![0];
+        let mut stack = vec![(&*self, 0, self.hasher(), 0)];
+        while let Some(top) = stack.last_mut() {
+            let circuit = top.0;
+            match circuit.operands.get(top.1) {
+                Some(operand) => {
+                    top.1 += 1;
+                    match operand {
+                        Operand::Variable(var) => {
+                            static_variables.add(var.clone());
+                            var.hash(&mut top.2);
+                        }
+                        Operand::NestedCircuit(nested_circuit) => {
+                            hashes.push(0);
+                            let position = hashes.len() - 1;
+                            stack.push((nested_circuit, 0, nested_circuit.hasher(), position));
+                        }
+                        Operand::Number(num) => num.hash(&mut top.2),
+                        Operand::Fixed(fixed) => fixed.hash(&mut top.2),
+                    }
+                }
+                None => {
+                    let (circuit, _, hasher, position) = stack.pop().unwrap();
+                    let circuit_hash = hasher.finish();
+                    hashes[position] = circuit_hash;
 
-        for operand in &mut self.operands {
-            match operand {
-                Operand::Variable(var) => {
-                    static_variables.add(var.clone());
-                    var.hash(&mut hasher);
+                    for index in 0..circuit.auxiliary_count() {
+                        linearization_variables
+                            .add(Circuit::auxiliary_variable(circuit_hash, index));
+                    }
+                    match stack.last_mut() {
+                        Some(parent) => {
+                            linearization_variables.add(circuit_hash.to_string());
+                            parent.2.write_u64(circuit_hash);
+                        }
+                        None => static_variables.add("out".to_string()),
+                    }
                 }
-                Operand::NestedCircuit(nested_circuit) => {
-                    let nested_hash = nested_circuit.hash_and_index_recursive(
-                        false,
-                        static_variables,
-                        linearization_variables,
-                    );
-                    hasher.write_u64(nested_hash);
+            }
+        }
+
+        let mut hashes = hashes.into_iter();
+        let mut stack = vec![self];
+        while let Some(circuit) = stack.pop() {
+            circuit.set_hash(hashes.next().unwrap());
+            for operand in circuit.operands.iter_mut().rev() {
+                if let Operand::NestedCircuit(nested_circuit) = operand {
+                    stack.push(nested_circuit);
                 }
-                Operand::Number(num) => num.hash(&mut hasher),
             }
         }
+    }
 
-        let circuit_hash = hasher.finish();
+    /// Hasher fed with the circuit's own attributes, before its operands.
+    fn hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        self.operation.hash(&mut hasher);
+        self.ty.hash(&mut hasher);
+        hasher
+    }
+}
 
-        self.set_hash(circuit_hash);
-        if root {
-            static_variables.add("out".to_string());
-        } else {
-            linearization_variables.add(circuit_hash.to_string());
+/// Drops nested circuits one at a time rather than recursively.
+impl Drop for Circuit {
+    fn drop(&mut self) {
+        let mut nested = Vec::new();
+        let mut operands = std::mem::take(&mut self.operands);
+        loop {
+            for operand in operands {
+                if let Operand::NestedCircuit(nested_circuit) = operand {
+                    nested.push(nested_circuit);
+                }
+            }
+            match nested.pop() {
+                Some(mut nested_circuit) => operands = std::mem::take(&mut nested_circuit.operands),
+                None => break,
+            }
         }
-
-        circuit_hash
     }
 }
 
@@ -128,4 +698,31 @@ mod tests {
         assert_eq!(circuit.operands.len(), 2);
         assert_eq!(circuit.hash, 0);
     }
+
+    #[test]
+    fn test_square_and_multiply_chain() {
+        assert!(square_and_multiply_chain(1).is_empty());
+        assert_eq!(square_and_multiply_chain(2), vec![false]);
+        // 13 = 0b1101: x -> x^2 -> x^3 -> x^6 -> x^12 -> x^13
+        assert_eq!(
+            square_and_multiply_chain(13),
+            vec![false, true, false, false, true]
+        );
+        assert_eq!(square_and_multiply_chain(1 << 20).len(), 20);
+    }
+
+    #[test]
+    fn test_bit_decomposition_allocates_auxiliary_variables() {
+        let mut circuit: Circuit =
+            serde_json::from_str(r#"{"operation": {"ToBits": {"n": 4}}, "operands": ["x"]}"#)
+                .unwrap();
+        assert!(circuit.is_valid());
+
+        let variable_map = circuit.hash_and_index_circuit();
+        assert_eq!(variable_map.len(), 3 + 4);
+        for index in 0..4 {
+            let name = Circuit::auxiliary_variable(circuit.get_hash(), index);
+            assert!(variable_map.get_index(&name).is_some());
+        }
+    }
 }
@@ -1,4 +1,6 @@
 mod circuit;
+pub use self::circuit::square_and_multiply_chain;
 pub use self::circuit::Circuit;
 pub use self::circuit::Operand;
 pub use self::circuit::Operation;
+pub use self::circuit::Type;
@@ -0,0 +1,269 @@
+use crate::circuits::circuit::{Circuit, Operand};
+use crate::circuits::errors::CircuitError;
+use serde_json::{Map, Value};
+
+/// Keys of template imports and loops, which only the `Loader` instantiates.
+const LOADER_KEYS: [&str; 3] = ["import", "repeat", "templates"];
+
+/// Deserializes a circuit from JSON, reading nested circuits with an explicit stack so that
+/// their depth is only bounded by memory. Fields other than operands are small and are read by
+/// `serde_json`.
+///
+/// Circuits using templates or loops are rejected with an `ImportError`, as they need a
+/// `Loader`.
+pub fn from_json(json: &str) -> Result<Circuit, CircuitError> {
+    let mut deserializer = Deserializer { json, position: 0 };
+    let circuit = deserializer.circuit()?;
+    deserializer.skip_whitespace();
+    if deserializer.position < json.len() {
+        return Err(deserializer.error("end of input"));
+    }
+    Ok(circuit)
+}
+
+/// An object being read: a circuit, unless it has neither operation nor operands and is
+/// another kind of operand such as a fixed-point literal.
+#[derive(Default)]
+struct Node {
+    fields: Map<String, Value>,
+    operands: Vec<Operand>,
+    has_operands: bool,
+}
+
+impl Node {
+    fn into_operand(self) -> Result<Operand, CircuitError> {
+        let deserialization_error =
+            |e: serde_json::Error| CircuitError::DeserializationError(e.to_string());
+        if !self.has_operands && !self.fields.contains_key("operation") {
+            return serde_json::from_value(Value::Object(self.fields))
+                .map_err(deserialization_error);
+        }
+
+        let mut fields = self.fields;
+        fields.insert("operands".to_string(), Value::Array(Vec::new()));
+        let mut circuit: Circuit =
+            serde_json::from_value(Value::Object(fields)).map_err(deserialization_error)?;
+        circuit.operands = self.operands;
+        Ok(Operand::NestedCircuit(Box::new(circuit)))
+    }
+}
+
+/// Part of an object the deserializer is in.
+enum State {
+    Fields,
+    Operands,
+}
+
+struct Deserializer<'a> {
+    json: &'a str,
+    position: usize,
+}
+
+impl<'a> Deserializer<'a> {
+    fn circuit(&mut self) -> Result<Circuit, CircuitError> {
+        self.expect(b'{')?;
+        let mut stack = vec![Node::default()];
+        let mut state = State::Fields;
+        // Whether the next field or operand is the first of its object or array.
+        let mut first = true;
+
+        loop {
+            match state {
+                State::Fields => {
+                    if self.consume(b'}') {
+                        let operand = stack.pop().unwrap().into_operand()?;
+                        match stack.last_mut() {
+                            Some(parent) => {
+                                parent.operands.push(operand);
+                                state = State::Operands;
+                                first = false;
+                            }
+                            None => {
+                                return match operand {
+                                    Operand::NestedCircuit(circuit) => Ok(*circuit),
+                                    _ => Err(CircuitError::DeserializationError(
+                                        "expected a circuit".to_string(),
+                                    )),
+                                }
+                            }
+                        }
+                        continue;
+                    }
+                    if !first {
+                        self.expect(b',')?;
+                    }
+                    first = false;
+
+                    let key: String = parse(self.scan()?)?;
+                    self.expect(b':')?;
+                    let node = stack.last_mut().unwrap();
+                    if LOADER_KEYS.contains(&key.as_str()) {
+                        return Err(CircuitError::ImportError(format!(
+                            "{} must be instantiated by a loader",
+                            key
+                        )));
+                    } else if key == "operands" {
+                        self.expect(b'[')?;
+                        node.has_operands = true;
+                        state = State::Operands;
+                        first = true;
+                    } else {
+                        let value = parse(self.scan()?)?;
+                        node.fields.insert(key, value);
+                    }
+                }
+                State::Operands => {
+                    if self.consume(b']') {
+                        state = State::Fields;
+                        first = false;
+                        continue;
+                    }
+                    if !first {
+                        self.expect(b',')?;
+                    }
+                    first = false;
+
+                    if self.consume(b'{') {
+                        stack.push(Node::default());
+                        state = State::Fields;
+                        first = true;
+                    } else {
+                        let operand = parse(self.scan()?)?;
+                        stack.last_mut().unwrap().operands.push(operand);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the next value without parsing it.
+    fn scan(&mut self) -> Result<&'a str, CircuitError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let bytes = self.json.as_bytes();
+        let mut depth = 0usize;
+
+        while let Some(&byte) = bytes.get(self.position) {
+            match byte {
+                b'"' => self.skip_string()?,
+                b'{' | b'[' => {
+                    depth += 1;
+                    self.position += 1;
+                }
+                b'}' | b']' if depth > 0 => {
+                    depth -= 1;
+                    self.position += 1;
+                }
+                b',' | b'}' | b']' if depth == 0 => break,
+                _ if depth == 0 && byte.is_ascii_whitespace() => break,
+                _ => self.position += 1,
+            }
+            if depth == 0 && matches!(byte, b'"' | b'}' | b']') {
+                break;
+            }
+        }
+
+        if self.position == start {
+            return Err(self.error("a value"));
+        }
+        Ok(&self.json[start..self.position])
+    }
+
+    fn skip_string(&mut self) -> Result<(), CircuitError> {
+        let bytes = self.json.as_bytes();
+        self.position += 1;
+        while let Some(&byte) = bytes.get(self.position) {
+            match byte {
+                b'\\' => self.position += 2,
+                b'"' => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => self.position += 1,
+            }
+        }
+        Err(self.error("the end of the string"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let bytes = self.json.as_bytes();
+        while bytes
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Skips `byte` if it comes next.
+    fn consume(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.json.as_bytes().get(self.position) == Some(&byte);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), CircuitError> {
+        if self.consume(byte) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", byte as char)))
+        }
+    }
+
+    fn error(&self, expected: &str) -> CircuitError {
+        CircuitError::DeserializationError(format!(
+            "expected {} at byte {}",
+            expected, self.position
+        ))
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, CircuitError> {
+    serde_json::from_str(json).map_err(|e| CircuitError::DeserializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_serde_deserialization() {
+        let json = r#"{"operation": {"FixedMul": {"scale": 16}},
+            "operands": [{"fixed": "1.5", "scale": 16},
+                         {"operation": "Select", "type": "u8",
+                          "operands": ["c", {"operation": "Sum", "operands": ["v"]}, -3]}],
+            "public_inputs": ["c", "v"],
+            "arrays": {"v": 2, "w": 1}}"#;
+        let mut streamed = from_json(json).unwrap();
+        let mut expected: Circuit = serde_json::from_str(json).unwrap();
+
+        let variables = streamed.hash_and_index_circuit();
+        assert_eq!(
+            variables.into_vector(),
+            expected.hash_and_index_circuit().into_vector()
+        );
+        assert_eq!(streamed.hash, expected.hash);
+    }
+
+    #[test]
+    fn test_rejects_malformed_json() {
+        for json in [
+            r#"{"operation": "Add", "operands": ["x" 1]}"#,
+            r#"{"operation": "Add", "operands": ["x", 1]"#,
+            r#"{"operation": "Add", "operands": ["x", 1]} 2"#,
+            r#"{"fixed": "1.5", "scale": 16}"#,
+        ] {
+            assert!(matches!(
+                from_json(json),
+                Err(CircuitError::DeserializationError(_))
+            ));
+        }
+        assert!(matches!(
+            from_json(r#"{"operation": "Add", "operands": [{"import": "a.json"}, 1]}"#),
+            Err(CircuitError::ImportError(_))
+        ));
+    }
+}
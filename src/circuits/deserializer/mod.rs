@@ -0,0 +1,2 @@
+mod deserializer;
+pub use self::deserializer::from_json;
@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum CircuitError {
     #[error("invalid operation")]
@@ -22,4 +23,10 @@ pub enum CircuitError {
 
     #[error("deserialization error: {0}")]
     DeserializationError(String),
+
+    #[error("import error: {0}")]
+    ImportError(String),
+
+    #[error("proof error: {0}")]
+    ProofError(String),
 }
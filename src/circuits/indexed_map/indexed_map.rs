@@ -46,4 +46,14 @@ impl<T: Eq + Hash + Clone> IndexedMap<T> {
     pub fn len(&self) -> usize {
         self.vector.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+}
+
+impl<T: Eq + Hash + Clone> Default for IndexedMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
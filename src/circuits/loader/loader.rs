@@ -0,0 +1,386 @@
+use crate::circuits::errors::CircuitError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key under which a circuit file defines its templates.
+const TEMPLATES: &str = "templates";
+/// Key of an operand instantiating a template.
+const IMPORT: &str = "import";
+/// Key of an operand unrolling a loop.
+const REPEAT: &str = "repeat";
+
+/// Loads circuit files, instantiating the templates they import.
+///
+/// An operand `{"import": "library.json#range", "params": {"bits": 8}, "inputs": {"x": "y"}}`
+/// stands for the template `range` defined under `"templates"` in `library.json`, a path
+/// relative to the importing file. Without a `#template` suffix the root circuit of the file
+/// is imported, and with an empty path a template of the importing file itself. Strings
+/// `"$bits"` anywhere in the template are replaced by the parameter, and variable operands
+/// named in `inputs` by the bound operands.
+///
+/// An operand `{"repeat": 3, "index": "i", "body": {...}}` unrolls into three operands, the
+/// body with `"$i"` replaced by 0, 1 and 2. With `"accumulator": "acc"` and `"initial": "x"` it
+/// instead folds into a single operand: the variable `acc` in each copy of the body stands for
+/// the previous copy, and for `x` in the first one.
+#[derive(Default)]
+pub struct Loader {
+    files: HashMap<PathBuf, Value>,
+    /// Templates being instantiated, outermost first.
+    stack: Vec<String>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the circuit file at `path` with all its imports instantiated.
+    pub fn load(&mut self, path: &Path) -> Result<Value, CircuitError> {
+        let path = canonicalize(path)?;
+        self.instantiate(&path, None, &Map::new(), &Map::new())
+    }
+
+    fn instantiate(
+        &mut self,
+        path: &Path,
+        template: Option<&str>,
+        params: &Map<String, Value>,
+        inputs: &Map<String, Value>,
+    ) -> Result<Value, CircuitError> {
+        let label = match template {
+            Some(name) => format!("{}#{}", path.display(), name),
+            None => path.display().to_string(),
+        };
+        if self.stack.contains(&label) {
+            let chain = [self.stack.as_slice(), &[label]].concat().join(" -> ");
+            return Err(CircuitError::ImportError(format!(
+                "cyclic import {}",
+                chain
+            )));
+        }
+
+        let file = self.read(path)?;
+        let mut body = match template {
+            Some(name) => file
+                .get(TEMPLATES)
+                .and_then(|templates| templates.get(name))
+                .cloned()
+                .ok_or_else(|| CircuitError::ImportError(format!("no template {}", label)))?,
+            None => {
+                let mut root = file.clone();
+                if let Value::Object(fields) = &mut root {
+                    fields.remove(TEMPLATES);
+                }
+                root
+            }
+        };
+        substitute_params(&mut body, params).map_err(|name| {
+            CircuitError::ImportError(format!("{} has no parameter {}", label, name))
+        })?;
+        substitute_inputs(&mut body, inputs);
+
+        self.stack.push(label);
+        let resolved = self.resolve(&mut body, path);
+        self.stack.pop();
+        resolved.map(|_| body)
+    }
+
+    /// Replaces the imports in a circuit read from `path` by their instantiations and unrolls
+    /// its loops.
+    fn resolve(&mut self, circuit: &mut Value, path: &Path) -> Result<(), CircuitError> {
+        let mut expanded = self.expand(std::mem::take(circuit), path)?;
+        if expanded.len() != 1 {
+            return Err(CircuitError::ImportError(
+                "a repeat without accumulator must be an operand".to_string(),
+            ));
+        }
+        *circuit = expanded.remove(0);
+        Ok(())
+    }
+
+    /// Operands an operand read from `path` stands for.
+    fn expand(&mut self, mut operand: Value, path: &Path) -> Result<Vec<Value>, CircuitError> {
+        if operand.get(IMPORT).is_some() {
+            return Ok(vec![self.import(&operand, path)?]);
+        }
+        if operand.get(REPEAT).is_some() {
+            return self.repeat(&operand, path);
+        }
+        if let Some(Value::Array(operands)) = operand.get_mut("operands") {
+            let mut expanded = Vec::new();
+            for operand in std::mem::take(operands) {
+                expanded.extend(self.expand(operand, path)?);
+            }
+            *operands = expanded;
+        }
+        Ok(vec![operand])
+    }
+
+    fn repeat(&mut self, operand: &Value, path: &Path) -> Result<Vec<Value>, CircuitError> {
+        let count = operand[REPEAT].as_u64().ok_or_else(|| {
+            CircuitError::ImportError(format!("repeat must be a count, found {}", operand[REPEAT]))
+        })?;
+        let body = operand
+            .get("body")
+            .ok_or_else(|| CircuitError::ImportError("repeat without body".to_string()))?;
+        let accumulator = match operand.get("accumulator") {
+            None => None,
+            Some(Value::String(name)) => Some(name.as_str()),
+            Some(value) => {
+                return Err(CircuitError::ImportError(format!(
+                    "accumulator must be a variable, found {}",
+                    value
+                )))
+            }
+        };
+
+        let mut accumulated = match accumulator {
+            Some(_) => {
+                let mut initial = operand.get("initial").cloned().ok_or_else(|| {
+                    CircuitError::ImportError("repeat with accumulator needs initial".to_string())
+                })?;
+                self.resolve(&mut initial, path)?;
+                Some(initial)
+            }
+            None => None,
+        };
+        let mut unrolled = Vec::new();
+        for i in 0..count {
+            let mut copy = body.clone();
+            let index = Map::from_iter([(index(operand), Value::from(i))]);
+            substitute_params(&mut copy, &index).map_err(|name| {
+                CircuitError::ImportError(format!("repeat has no parameter {}", name))
+            })?;
+            if let (Some(name), Some(previous)) = (accumulator, accumulated.take()) {
+                substitute_operand(&mut copy, &Map::from_iter([(name.to_string(), previous)]));
+            }
+
+            self.resolve(&mut copy, path)?;
+            match accumulator {
+                Some(_) => accumulated = Some(copy),
+                None => unrolled.push(copy),
+            }
+        }
+        Ok(accumulated.map_or(unrolled, |value| vec![value]))
+    }
+
+    fn import(&mut self, operand: &Value, path: &Path) -> Result<Value, CircuitError> {
+        let reference = operand[IMPORT].as_str().ok_or_else(|| {
+            CircuitError::ImportError(format!("import must be a path, found {}", operand[IMPORT]))
+        })?;
+        let params = object(operand, "params")?;
+        let mut inputs = object(operand, "inputs")?;
+        // Bound operands belong to the importing file.
+        for input in inputs.values_mut() {
+            self.resolve(input, path)?;
+        }
+
+        let (file, template) = match reference.split_once('#') {
+            Some((file, template)) => (file, Some(template)),
+            None => (reference, None),
+        };
+        let target = if file.is_empty() {
+            path.to_path_buf()
+        } else {
+            canonicalize(&path.parent().unwrap_or(Path::new("")).join(file))?
+        };
+        self.instantiate(&target, template, &params, &inputs)
+    }
+
+    fn read(&mut self, path: &Path) -> Result<&Value, CircuitError> {
+        if !self.files.contains_key(path) {
+            let json = fs::read_to_string(path)
+                .map_err(|e| CircuitError::FileReadError(format!("{}: {}", path.display(), e)))?;
+            let value = serde_json::from_str(&json)
+                .map_err(|e| CircuitError::DeserializationError(e.to_string()))?;
+            self.files.insert(path.to_path_buf(), value);
+        }
+        Ok(&self.files[path])
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, CircuitError> {
+    fs::canonicalize(path)
+        .map_err(|e| CircuitError::FileReadError(format!("{}: {}", path.display(), e)))
+}
+
+fn object(operand: &Value, key: &str) -> Result<Map<String, Value>, CircuitError> {
+    match operand.get(key) {
+        None => Ok(Map::new()),
+        Some(Value::Object(fields)) => Ok(fields.clone()),
+        Some(value) => Err(CircuitError::ImportError(format!(
+            "{} must be an object, found {}",
+            key, value
+        ))),
+    }
+}
+
+/// Name of the index variable of a loop.
+fn index(repeat: &Value) -> String {
+    repeat
+        .get("index")
+        .and_then(Value::as_str)
+        .unwrap_or("i")
+        .to_string()
+}
+
+/// Replaces the `"$name"` strings in `value` by the parameters, returning the name of the
+/// first parameter missing. Index variables of loops are left for the loops to replace.
+fn substitute_params(value: &mut Value, params: &Map<String, Value>) -> Result<(), String> {
+    let mut scoped = None;
+    if value.get(REPEAT).is_some() {
+        let name = index(value);
+        let mut shadowed = params.clone();
+        shadowed.insert(name.clone(), Value::String(format!("${}", name)));
+        scoped = Some(shadowed);
+    }
+
+    match value {
+        Value::String(string) => {
+            if let Some(name) = string.strip_prefix('$') {
+                *value = params.get(name).cloned().ok_or_else(|| name.to_string())?;
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                substitute_params(value, params)?;
+            }
+        }
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                match (key.as_str(), &scoped) {
+                    ("body", Some(scoped)) => substitute_params(value, scoped)?,
+                    _ => substitute_params(value, params)?,
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replaces the variable operands bound in `inputs`, including those passed on to imports
+/// and loops. Bound operands are inserted as they are.
+fn substitute_inputs(circuit: &mut Value, inputs: &Map<String, Value>) {
+    if circuit.get(REPEAT).is_some() {
+        let mut scoped = inputs.clone();
+        if let Some(accumulator) = circuit.get("accumulator").and_then(Value::as_str) {
+            scoped.remove(accumulator);
+        }
+        if let Some(initial) = circuit.get_mut("initial") {
+            substitute_operand(initial, inputs);
+        }
+        if let Some(body) = circuit.get_mut("body") {
+            substitute_operand(body, &scoped);
+        }
+        return;
+    }
+
+    let operands: Vec<&mut Value> = if circuit.get(IMPORT).is_some() {
+        match circuit.get_mut("inputs") {
+            Some(Value::Object(fields)) => fields.values_mut().collect(),
+            _ => Vec::new(),
+        }
+    } else {
+        match circuit.get_mut("operands") {
+            Some(Value::Array(operands)) => operands.iter_mut().collect(),
+            _ => Vec::new(),
+        }
+    };
+    for operand in operands {
+        substitute_operand(operand, inputs);
+    }
+}
+
+fn substitute_operand(operand: &mut Value, inputs: &Map<String, Value>) {
+    match operand.as_str().and_then(|name| inputs.get(name)) {
+        Some(bound) => *operand = bound.clone(),
+        None => substitute_inputs(operand, inputs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::Circuit;
+
+    fn sample(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/circuits/sample_circuits/templates")
+            .join(name)
+    }
+
+    #[test]
+    fn test_templates_are_instantiated() {
+        let mut circuit = Circuit::from_file(sample("templated.json").to_str().unwrap()).unwrap();
+        let mut expected: Circuit = serde_json::from_str(
+            r#"{"operation": "Add",
+                "operands": [
+                    {"operation": {"RangeCheck": {"bits": 8}},
+                     "operands": [{"operation": "Add", "operands": ["y", "y"]}]},
+                    {"operation": "Add",
+                     "operands": [{"operation": "Multiply", "operands": ["y", "y"]},
+                                  {"operation": "Multiply", "operands": [3, 3]}]}]}"#,
+        )
+        .unwrap();
+
+        let variables = circuit.hash_and_index_circuit();
+        assert_eq!(
+            variables.into_vector(),
+            expected.hash_and_index_circuit().into_vector()
+        );
+        assert_eq!(circuit.hash, expected.hash);
+    }
+
+    #[test]
+    fn test_cyclic_imports_are_rejected() {
+        let error = Loader::new().load(&sample("cycle_a.json")).unwrap_err();
+        match error {
+            CircuitError::ImportError(message) => {
+                assert!(message.starts_with("cyclic import"));
+                assert!(message.ends_with("cycle_a.json"));
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_missing_parameters_are_rejected() {
+        let error = Loader::new()
+            .instantiate(
+                &sample("library.json"),
+                Some("range"),
+                &Map::new(),
+                &Map::new(),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(error, CircuitError::ImportError(message) if message.ends_with("has no parameter bits"))
+        );
+    }
+
+    #[test]
+    fn test_loops_are_unrolled() {
+        let mut circuit = Circuit::from_file(sample("repeated.json").to_str().unwrap()).unwrap();
+        let mut expected: Circuit = serde_json::from_str(
+            r#"{"operation": "Add",
+                "operands": [
+                    {"operation": "Sum",
+                     "operands": [{"operation": "Multiply", "operands": ["x", 0]},
+                                  {"operation": "Multiply", "operands": ["x", 1]},
+                                  {"operation": "Multiply", "operands": ["x", 2]}]},
+                    {"operation": "Multiply",
+                     "operands": [{"operation": "Multiply", "operands": ["y", "y"]}, "y"]}]}"#,
+        )
+        .unwrap();
+
+        let variables = circuit.hash_and_index_circuit();
+        assert_eq!(
+            variables.into_vector(),
+            expected.hash_and_index_circuit().into_vector()
+        );
+        assert_eq!(circuit.hash, expected.hash);
+    }
+}
@@ -1,8 +1,15 @@
 mod circuit;
+mod deserializer;
 mod errors;
 mod indexed_map;
+mod loader;
+mod utils;
 
+pub use circuit::square_and_multiply_chain;
 pub use circuit::Circuit;
 pub use circuit::Operand;
 pub use circuit::Operation;
+pub use circuit::Type;
+pub use errors::CircuitError;
 pub use indexed_map::IndexedMap;
+pub use utils::parse_inputs;
@@ -0,0 +1,2 @@
+mod utils;
+pub use self::utils::parse_inputs;
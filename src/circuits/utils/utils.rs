@@ -0,0 +1,85 @@
+use crate::circuits::errors::CircuitError;
+use crate::circuits::Circuit;
+use crate::utils::field::field::FieldElement;
+use crate::utils::fixed::fixed::FixedPoint;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Parses witness inputs from a JSON object. Values are integers, decimal strings for field
+/// elements beyond `i64`, fixed-point literals such as `{"fixed": "2.5", "scale": 16}`, or
+/// arrays, whose elements are named `v[i]`.
+pub fn parse_inputs(json: &str) -> Result<HashMap<String, FieldElement>, CircuitError> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| CircuitError::DeserializationError(e.to_string()))?;
+    let object = value.as_object().ok_or_else(|| {
+        CircuitError::DeserializationError("inputs must be a JSON object".to_string())
+    })?;
+
+    let mut inputs = HashMap::new();
+    for (name, value) in object {
+        insert_input(&mut inputs, name.clone(), value)?;
+    }
+    Ok(inputs)
+}
+
+fn insert_input(
+    inputs: &mut HashMap<String, FieldElement>,
+    name: String,
+    value: &Value,
+) -> Result<(), CircuitError> {
+    let invalid = || CircuitError::DeserializationError(format!("invalid value for {}", name));
+
+    let element = match value {
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                insert_input(inputs, Circuit::element(&name, index), element)?;
+            }
+            return Ok(());
+        }
+        Value::Number(number) => FieldElement::from(number.as_i64().ok_or_else(invalid)?),
+        Value::String(digits) => FieldElement::from_str(digits).map_err(|_| invalid())?,
+        Value::Object(_) => {
+            let literal: FixedPoint = serde_json::from_value(value.clone()).map_err(|e| {
+                CircuitError::DeserializationError(format!("invalid value for {}: {}", name, e))
+            })?;
+            literal.encode()
+        }
+        _ => return Err(invalid()),
+    };
+    inputs.insert(name, element);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrays_are_flattened() {
+        let inputs = parse_inputs(r#"{"x": -2, "v": [1, "7"], "m": [[3], [4]]}"#).unwrap();
+
+        assert_eq!(inputs.len(), 5);
+        assert_eq!(inputs["x"], -FieldElement::from(2));
+        assert_eq!(inputs["v[1]"], FieldElement::from(7));
+        assert_eq!(inputs["m[1][0]"], FieldElement::from(4));
+        assert!(parse_inputs(r#"{"x": true}"#).is_err());
+    }
+
+    #[test]
+    fn test_fixed_point_literals_are_encoded() {
+        let inputs = parse_inputs(r#"{"p": {"fixed": "-1.5", "scale": 4}}"#).unwrap();
+        assert_eq!(inputs["p"], -FieldElement::from(24));
+
+        for json in [
+            r#"{"p": {"fixed": "1.2.3", "scale": 4}}"#,
+            r#"{"p": {"fixed": "1", "scale": 200}}"#,
+            r#"{"p": {"value": "1"}}"#,
+        ] {
+            assert!(matches!(
+                parse_inputs(json),
+                Err(CircuitError::DeserializationError(_))
+            ));
+        }
+    }
+}
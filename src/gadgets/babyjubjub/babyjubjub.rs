@@ -0,0 +1,175 @@
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::babyjubjub::babyjubjub::{Point, A, D};
+use crate::utils::field::field::FieldElement;
+use ark_ff::Field;
+
+/// Baby Jubjub point whose coordinates are circuit signals.
+#[derive(Debug, Clone)]
+pub struct PointSignal {
+    pub x: Signal,
+    pub y: Signal,
+}
+
+impl PointSignal {
+    pub fn constant(point: &Point) -> Self {
+        PointSignal {
+            x: Signal::constant(point.x),
+            y: Signal::constant(point.y),
+        }
+    }
+}
+
+/// Enforces `a x^2 + y^2 = 1 + d x^2 y^2`, three constraints.
+pub fn assert_on_curve<CS: ConstraintSystem>(cs: &mut CS, point: &PointSignal) {
+    let x2 = cs.multiply(&point.x, &point.x);
+    let y2 = cs.multiply(&point.y, &point.y);
+    let left = &(&(&x2 * FieldElement::from(A)) + &y2) - &Signal::one();
+    cs.enforce(&(&x2 * FieldElement::from(D)), &y2, &left);
+}
+
+/// Allocates `numerator / denominator`, one constraint. The denominators of the complete
+/// addition law never vanish on curve points, and a vanishing one leaves the constraint
+/// unsatisfied.
+fn divide<CS: ConstraintSystem>(cs: &mut CS, numerator: &Signal, denominator: &Signal) -> Signal {
+    let value = numerator
+        .value
+        .zip(denominator.value)
+        .map(|(n, d)| n * d.inverse().unwrap_or_default());
+    let quotient = cs.allocate(value);
+    cs.enforce(&quotient, denominator, numerator);
+    quotient
+}
+
+/// Completes the addition law from `beta = x1 y2`, `gamma = y1 x2` and
+/// `delta = (y1 - a x1)(x2 + y2)`, three constraints.
+fn finish_addition<CS: ConstraintSystem>(
+    cs: &mut CS,
+    beta: &Signal,
+    gamma: &Signal,
+    delta: &Signal,
+) -> PointSignal {
+    let tau = &cs.multiply(beta, gamma) * FieldElement::from(D);
+    let x = divide(cs, &(beta + gamma), &(&Signal::one() + &tau));
+    let numerator = &(delta + &(beta * FieldElement::from(A))) - gamma;
+    let y = divide(cs, &numerator, &(&Signal::one() - &tau));
+    PointSignal { x, y }
+}
+
+/// Complete twisted Edwards addition, six constraints.
+pub fn add<CS: ConstraintSystem>(cs: &mut CS, p: &PointSignal, q: &PointSignal) -> PointSignal {
+    let beta = cs.multiply(&p.x, &q.y);
+    let gamma = cs.multiply(&p.y, &q.x);
+    let delta = cs.multiply(&(&p.y - &(&p.x * FieldElement::from(A))), &(&q.x + &q.y));
+    finish_addition(cs, &beta, &gamma, &delta)
+}
+
+/// Multiplies by the cofactor 8 with three doublings, eighteen constraints.
+pub fn clear_cofactor<CS: ConstraintSystem>(cs: &mut CS, point: &PointSignal) -> PointSignal {
+    let mut point = point.clone();
+    for _ in 0..3 {
+        point = add(cs, &point, &point);
+    }
+    point
+}
+
+/// Adds a constant point, three constraints since the products with its coordinates are linear.
+pub fn add_constant<CS: ConstraintSystem>(cs: &mut CS, p: &PointSignal, q: &Point) -> PointSignal {
+    let beta = &p.x * q.y;
+    let gamma = &p.y * q.x;
+    let delta = &(&p.y - &(&p.x * FieldElement::from(A))) * (q.x + q.y);
+    finish_addition(cs, &beta, &gamma, &delta)
+}
+
+/// `p` when the boolean `bit` is 1 and `q` otherwise, two constraints.
+pub fn select<CS: ConstraintSystem>(
+    cs: &mut CS,
+    bit: &Signal,
+    p: &PointSignal,
+    q: &PointSignal,
+) -> PointSignal {
+    PointSignal {
+        x: &q.x + &cs.multiply(bit, &(&p.x - &q.x)),
+        y: &q.y + &cs.multiply(bit, &(&p.y - &q.y)),
+    }
+}
+
+/// Multiplies `point` by the integer with the given little-endian boolean bits, using
+/// double-and-add with fourteen constraints per bit.
+pub fn scalar_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    point: &PointSignal,
+    bits: &[Signal],
+) -> PointSignal {
+    let mut acc = PointSignal::constant(&Point::identity());
+    for bit in bits.iter().rev() {
+        acc = add(cs, &acc, &acc);
+        let sum = add(cs, &acc, point);
+        acc = select(cs, bit, &sum, &acc);
+    }
+    acc
+}
+
+/// Multiplies a constant point by the integer with the given little-endian boolean bits, adding
+/// the precomputed `2^i * base` for every set bit at five constraints per bit.
+pub fn fixed_base_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    base: &Point,
+    bits: &[Signal],
+) -> PointSignal {
+    let mut acc = PointSignal::constant(&Point::identity());
+    let mut power = *base;
+    for bit in bits {
+        let sum = add_constant(cs, &acc, &power);
+        acc = select(cs, bit, &sum, &acc);
+        power = power.double();
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::boolean::to_bits;
+    use crate::gadgets::{CostCounter, Evaluator};
+    use crate::utils::babyjubjub::babyjubjub::{to_field, Scalar, SCALAR_BITS};
+
+    fn point_value(point: &PointSignal) -> Point {
+        Point {
+            x: point.x.value.unwrap(),
+            y: point.y.value.unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_scalar_multiplication_matches_native() {
+        let mut cs = Evaluator;
+        let scalar = Scalar::from(0xdead_beef_u64) * Scalar::from(0x1234_5678_9abc_u64);
+        let bits = to_bits(&mut cs, &Signal::from_value(to_field(&scalar)), SCALAR_BITS);
+
+        let base = Point::base8().mul(&Scalar::from(7u64));
+        let expected = base.mul(&scalar);
+        let variable = scalar_mul(&mut cs, &PointSignal::constant(&base), &bits);
+        let fixed = fixed_base_mul(&mut cs, &base, &bits);
+
+        assert_eq!(point_value(&variable), expected);
+        assert_eq!(point_value(&fixed), expected);
+        assert_on_curve(&mut cs, &fixed);
+    }
+
+    #[test]
+    fn test_costs() {
+        let bits = vec![Signal::default(); 8];
+        let point = PointSignal {
+            x: Signal::default(),
+            y: Signal::default(),
+        };
+
+        let mut counter = CostCounter::default();
+        scalar_mul(&mut counter, &point, &bits);
+        assert_eq!(counter.constraints, 8 * 14);
+
+        let mut counter = CostCounter::default();
+        fixed_base_mul(&mut counter, &Point::base8(), &bits);
+        assert_eq!(counter.constraints, 8 * 5);
+    }
+}
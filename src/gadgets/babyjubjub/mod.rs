@@ -0,0 +1,2 @@
+mod babyjubjub;
+pub use self::babyjubjub::*;
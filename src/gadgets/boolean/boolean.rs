@@ -0,0 +1,233 @@
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::{power_of_two, to_bits_le, FieldElement, MODULUS_BITS};
+use ark_ff::Field;
+
+/// Decomposes `value` into `n` little-endian boolean signals, enforcing booleanity of every bit
+/// and that they recompose to `value`.
+pub fn to_bits<CS: ConstraintSystem>(cs: &mut CS, value: &Signal, n: usize) -> Vec<Signal> {
+    let bit_values = value.value.map(|value| to_bits_le(&value, n));
+
+    let bits: Vec<Signal> = (0..n)
+        .map(|i| {
+            let bit = cs.allocate(bit_values.as_ref().map(|bits| FieldElement::from(bits[i])));
+            cs.assert_boolean(&bit);
+            bit
+        })
+        .collect();
+    cs.assert_equal(&from_bits(&bits), value);
+    bits
+}
+
+/// Decomposes `value` into `MODULUS_BITS` bits encoding its canonical representative, ruling
+/// out the alias `value + p` that also fits in that many bits.
+pub fn to_bits_strict<CS: ConstraintSystem>(cs: &mut CS, value: &Signal) -> Vec<Signal> {
+    let bits = to_bits(cs, value, MODULUS_BITS);
+    assert_at_most(
+        cs,
+        &bits,
+        &to_bits_le(&-FieldElement::from(1), MODULUS_BITS),
+    );
+    bits
+}
+
+/// Enforces that the little-endian boolean `bits` encode an integer no larger than the constant
+/// given by its little-endian bits, one constraint per bit.
+pub fn assert_at_most<CS: ConstraintSystem>(cs: &mut CS, bits: &[Signal], constant: &[bool]) {
+    assert_eq!(bits.len(), constant.len());
+
+    // Product of the bits at the constant's set positions from the most significant end, 1
+    // while the bits scanned so far equal the constant's.
+    let mut equal_prefix: Option<Signal> = None;
+    for (bit, &set) in bits.iter().zip(constant).rev() {
+        if set {
+            equal_prefix = Some(match equal_prefix {
+                Some(prefix) => cs.multiply(&prefix, bit),
+                None => bit.clone(),
+            });
+        } else {
+            // A set bit where the constant has a zero exceeds it unless a lower bit came first.
+            let prefix = equal_prefix.clone().unwrap_or_else(Signal::one);
+            cs.enforce(&prefix, bit, &Signal::zero());
+        }
+    }
+}
+
+/// Recomposes little-endian bits into a single signal, without any constraint.
+pub fn from_bits(bits: &[Signal]) -> Signal {
+    bits.iter()
+        .enumerate()
+        .fold(Signal::zero(), |sum, (i, bit)| {
+            &sum + &(bit * power_of_two(i))
+        })
+}
+
+/// The `n` low bits of `value` as constant signals, little-endian.
+pub fn constant_bits(value: u64, n: usize) -> Vec<Signal> {
+    (0..n)
+        .map(|i| Signal::constant(FieldElement::from((value >> i) & 1)))
+        .collect()
+}
+
+/// `a XOR b = a + b - 2ab`, one constraint.
+pub fn xor<CS: ConstraintSystem>(cs: &mut CS, a: &Signal, b: &Signal) -> Signal {
+    let value = a.value.zip(b.value).map(|(a, b)| a + b - (a * b).double());
+    let result = cs.allocate(value);
+    cs.enforce(&(a * FieldElement::from(2)), b, &(&(a + b) - &result));
+    result
+}
+
+/// `a AND b = ab`, one constraint.
+pub fn and<CS: ConstraintSystem>(cs: &mut CS, a: &Signal, b: &Signal) -> Signal {
+    cs.multiply(a, b)
+}
+
+/// `NOT a = 1 - a`, linear.
+pub fn not(a: &Signal) -> Signal {
+    &Signal::one() - a
+}
+
+/// `a` when `condition` is 1 and `b` when it is 0, enforcing that `condition` is boolean: the
+/// two constraints of the `Select` operation.
+pub fn select<CS: ConstraintSystem>(
+    cs: &mut CS,
+    condition: &Signal,
+    a: &Signal,
+    b: &Signal,
+) -> Signal {
+    cs.assert_boolean(condition);
+    b + &cs.multiply(condition, &(a - b))
+}
+
+pub fn xor_bits<CS: ConstraintSystem>(cs: &mut CS, a: &[Signal], b: &[Signal]) -> Vec<Signal> {
+    a.iter().zip(b).map(|(a, b)| xor(cs, a, b)).collect()
+}
+
+pub fn and_bits<CS: ConstraintSystem>(cs: &mut CS, a: &[Signal], b: &[Signal]) -> Vec<Signal> {
+    a.iter().zip(b).map(|(a, b)| and(cs, a, b)).collect()
+}
+
+pub fn not_bits(a: &[Signal]) -> Vec<Signal> {
+    a.iter().map(not).collect()
+}
+
+/// Rotates little-endian bits towards the least significant end.
+pub fn rotate_right(bits: &[Signal], n: usize) -> Vec<Signal> {
+    let mut rotated = bits.to_vec();
+    rotated.rotate_left(n % bits.len());
+    rotated
+}
+
+/// Shifts little-endian bits towards the least significant end, filling with zeros.
+pub fn shift_right(bits: &[Signal], n: usize) -> Vec<Signal> {
+    (0..bits.len())
+        .map(|i| bits.get(i + n).cloned().unwrap_or_else(Signal::zero))
+        .collect()
+}
+
+/// Shifts little-endian bits towards the most significant end, dropping the overflow.
+pub fn shift_left(bits: &[Signal], n: usize) -> Vec<Signal> {
+    (0..bits.len())
+        .map(|i| {
+            if i >= n {
+                bits[i - n].clone()
+            } else {
+                Signal::zero()
+            }
+        })
+        .collect()
+}
+
+/// Adds words modulo `2^width` by decomposing their field sum, carry bits included.
+pub fn add_words<CS: ConstraintSystem>(cs: &mut CS, words: &[&[Signal]]) -> Vec<Signal> {
+    let width = words[0].len();
+    let carry_bits = (usize::BITS - (words.len() - 1).leading_zeros()) as usize;
+
+    let sum = words
+        .iter()
+        .fold(Signal::zero(), |sum, word| &sum + &from_bits(word));
+    let mut bits = to_bits(cs, &sum, width + carry_bits);
+    bits.truncate(width);
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{CostCounter, Evaluator};
+
+    fn word(value: u64, width: usize) -> Vec<Signal> {
+        (0..width)
+            .map(|i| Signal::from_value(FieldElement::from((value >> i) & 1)))
+            .collect()
+    }
+
+    fn value_of(bits: &[Signal]) -> FieldElement {
+        from_bits(bits).value.unwrap()
+    }
+
+    #[test]
+    fn test_bitwise_operations() {
+        let mut cs = Evaluator;
+        let (a, b) = (word(0b1100, 4), word(0b1010, 4));
+
+        assert_eq!(
+            value_of(&xor_bits(&mut cs, &a, &b)),
+            FieldElement::from(0b0110)
+        );
+        assert_eq!(
+            value_of(&and_bits(&mut cs, &a, &b)),
+            FieldElement::from(0b1000)
+        );
+        assert_eq!(value_of(&not_bits(&a)), FieldElement::from(0b0011));
+        assert_eq!(value_of(&rotate_right(&a, 1)), FieldElement::from(0b0110));
+        assert_eq!(value_of(&rotate_right(&a, 3)), FieldElement::from(0b1001));
+        assert_eq!(value_of(&shift_right(&a, 3)), FieldElement::from(0b0001));
+        assert_eq!(value_of(&shift_left(&a, 1)), FieldElement::from(0b1000));
+    }
+
+    #[test]
+    fn test_select() {
+        let (a, b) = (word(5, 1)[0].clone(), word(0, 1)[0].clone());
+        assert_eq!(select(&mut Evaluator, &a, &a, &b).value, a.value);
+        assert_eq!(select(&mut Evaluator, &b, &a, &b).value, b.value);
+
+        let mut counter = CostCounter::default();
+        select(&mut counter, &a, &a, &b);
+        assert_eq!(counter.constraints, 2);
+    }
+
+    #[test]
+    fn test_add_words_wraps_around() {
+        let mut cs = Evaluator;
+        let words = [word(0xffff_fff0, 32), word(0x20, 32), word(0xffff_ffff, 32)];
+        let slices: Vec<&[Signal]> = words.iter().map(|word| word.as_slice()).collect();
+
+        let sum = add_words(&mut cs, &slices);
+        let expected = 0xffff_fff0u32.wrapping_add(0x20).wrapping_add(0xffff_ffff);
+        assert_eq!(value_of(&sum), FieldElement::from(expected));
+
+        let mut counter = CostCounter::default();
+        add_words(&mut counter, &slices);
+        assert_eq!(counter.variables, 34);
+        assert_eq!(counter.constraints, 35);
+    }
+
+    #[test]
+    fn test_assert_at_most() {
+        let constant = to_bits_le(&FieldElement::from(0b1010), 4);
+        for value in 0..=0b1010 {
+            assert_at_most(&mut Evaluator, &word(value, 4), &constant);
+        }
+
+        let mut counter = CostCounter::default();
+        assert_at_most(&mut counter, &word(0, 4), &constant);
+        assert_eq!(counter.constraints, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_at_most_rejects_larger_values() {
+        let constant = to_bits_le(&FieldElement::from(0b1010), 4);
+        assert_at_most(&mut Evaluator, &word(0b1011, 4), &constant);
+    }
+}
@@ -0,0 +1,2 @@
+mod boolean;
+pub use self::boolean::*;
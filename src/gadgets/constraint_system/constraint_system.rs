@@ -0,0 +1,175 @@
+use crate::r1cs::r1cs::{normalize, LinearCombination};
+use crate::utils::field::field::FieldElement;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A value flowing through a gadget: the linear combination of circuit variables it is bound to,
+/// together with its assignment when a witness is being computed.
+#[derive(Debug, Clone, Default)]
+pub struct Signal {
+    pub lc: LinearCombination,
+    pub value: Option<FieldElement>,
+}
+
+impl Signal {
+    pub fn from_lc(lc: LinearCombination) -> Self {
+        Signal { lc, value: None }
+    }
+
+    pub fn from_value(value: FieldElement) -> Self {
+        Signal {
+            lc: Vec::new(),
+            value: Some(value),
+        }
+    }
+
+    pub fn constant(value: FieldElement) -> Self {
+        Signal {
+            lc: vec![(0, value)],
+            value: Some(value),
+        }
+    }
+
+    pub fn zero() -> Self {
+        Signal::constant(FieldElement::from(0))
+    }
+
+    pub fn one() -> Self {
+        Signal::constant(FieldElement::from(1))
+    }
+}
+
+impl Add for &Signal {
+    type Output = Signal;
+
+    fn add(self, other: &Signal) -> Signal {
+        Signal {
+            lc: normalize([self.lc.clone(), other.lc.clone()].concat()),
+            value: self.value.zip(other.value).map(|(a, b)| a + b),
+        }
+    }
+}
+
+impl Sub for &Signal {
+    type Output = Signal;
+
+    fn sub(self, other: &Signal) -> Signal {
+        self + &-other
+    }
+}
+
+impl Neg for &Signal {
+    type Output = Signal;
+
+    fn neg(self) -> Signal {
+        self * -FieldElement::from(1)
+    }
+}
+
+impl Mul<FieldElement> for &Signal {
+    type Output = Signal;
+
+    fn mul(self, scalar: FieldElement) -> Signal {
+        Signal {
+            lc: normalize(
+                self.lc
+                    .iter()
+                    .map(|&(index, coeff)| (index, coeff * scalar))
+                    .collect(),
+            ),
+            value: self.value.map(|value| value * scalar),
+        }
+    }
+}
+
+/// Sink for the variables and constraints emitted by a gadget.
+///
+/// The same gadget code runs against different systems: one counting the auxiliary variables it
+/// needs while the circuit is indexed, one writing its constraints into the R1CS and one
+/// recording the auxiliary values in the witness. Gadgets must therefore allocate the same
+/// variables whatever the values of their inputs.
+pub trait ConstraintSystem {
+    /// Allocates the next auxiliary variable, assigned to `value` when a witness is computed.
+    fn allocate(&mut self, value: Option<FieldElement>) -> Signal;
+
+    /// Enforces `a * b = c`.
+    fn enforce(&mut self, a: &Signal, b: &Signal, c: &Signal);
+
+    fn multiply(&mut self, a: &Signal, b: &Signal) -> Signal {
+        let product = self.allocate(a.value.zip(b.value).map(|(a, b)| a * b));
+        self.enforce(a, b, &product);
+        product
+    }
+
+    fn assert_equal(&mut self, a: &Signal, b: &Signal) {
+        self.enforce(&(a - b), &Signal::one(), &Signal::zero());
+    }
+
+    /// Enforces `a * (a - 1) = 0`.
+    fn assert_boolean(&mut self, a: &Signal) {
+        self.enforce(a, &(a - &Signal::one()), &Signal::zero());
+    }
+}
+
+/// Counts the auxiliary variables and constraints a gadget emits without recording them.
+#[derive(Debug, Default)]
+pub struct CostCounter {
+    pub variables: usize,
+    pub constraints: usize,
+}
+
+impl ConstraintSystem for CostCounter {
+    fn allocate(&mut self, _value: Option<FieldElement>) -> Signal {
+        self.variables += 1;
+        Signal::default()
+    }
+
+    fn enforce(&mut self, _a: &Signal, _b: &Signal, _c: &Signal) {
+        self.constraints += 1;
+    }
+}
+
+/// Evaluates gadgets on concrete values, checking every constraint as it is emitted.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct Evaluator;
+
+#[cfg(test)]
+impl ConstraintSystem for Evaluator {
+    fn allocate(&mut self, value: Option<FieldElement>) -> Signal {
+        Signal::from_value(value.unwrap())
+    }
+
+    fn enforce(&mut self, a: &Signal, b: &Signal, c: &Signal) {
+        assert_eq!(
+            a.value.unwrap() * b.value.unwrap(),
+            c.value.unwrap(),
+            "Unsatisfiable constraint"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_arithmetic_merges_terms() {
+        let x = Signal {
+            lc: vec![(1, FieldElement::from(1))],
+            value: Some(FieldElement::from(3)),
+        };
+        let y = Signal {
+            lc: vec![(2, FieldElement::from(1))],
+            value: Some(FieldElement::from(4)),
+        };
+
+        let sum = &(&(&x * FieldElement::from(2)) + &y) - &x;
+        assert_eq!(
+            sum.lc,
+            vec![(1, FieldElement::from(1)), (2, FieldElement::from(1))]
+        );
+        assert_eq!(sum.value, Some(FieldElement::from(7)));
+
+        assert!((&x - &x).lc.is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+mod constraint_system;
+pub use self::constraint_system::CostCounter;
+#[cfg(test)]
+pub use self::constraint_system::Evaluator;
+pub use self::constraint_system::ConstraintSystem;
+pub use self::constraint_system::Signal;
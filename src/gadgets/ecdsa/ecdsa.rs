@@ -0,0 +1,88 @@
+use crate::gadgets::nonnative::{assert_canonical, assert_equal, inverse, mul, reduce, NonNative};
+use crate::gadgets::secp256k1::{add, assert_on_curve, fixed_base_mul, scalar_mul, PointSignal};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::secp256k1::secp256k1::{base_modulus, group_order};
+use k256::ProjectivePoint;
+
+/// Verifies a secp256k1 ECDSA signature `(r, s)` on a prehashed message under `public_key`.
+/// Every integer is given as little-endian 64-bit limbs. Enforces `0 < r, s < n`, that the key
+/// lies on the curve, and that the x-coordinate of `(z / s) G + (r / s) Q` is `r` modulo `n`.
+pub fn ecdsa_verify<CS: ConstraintSystem>(
+    cs: &mut CS,
+    prehash: &[Signal],
+    r: &[Signal],
+    s: &[Signal],
+    public_key: (&[Signal], &[Signal]),
+) {
+    let order = group_order();
+
+    let z = NonNative::from_limbs(cs, prehash);
+    let r = NonNative::from_limbs(cs, r);
+    let s = NonNative::from_limbs(cs, s);
+    assert_canonical(cs, &r, &order);
+    assert_canonical(cs, &s, &order);
+    inverse(cs, &r, &order);
+    let s_inverse = inverse(cs, &s, &order);
+
+    let public_key = PointSignal {
+        x: NonNative::from_limbs(cs, public_key.0),
+        y: NonNative::from_limbs(cs, public_key.1),
+    };
+    assert_on_curve(cs, &public_key);
+
+    let u1 = mul(cs, &z, &s_inverse, &order);
+    let u2 = mul(cs, &r, &s_inverse, &order);
+    let u1_bits = u1.to_bits(cs);
+    let u2_bits = u2.to_bits(cs);
+    let p1 = fixed_base_mul(cs, &ProjectivePoint::GENERATOR, &u1_bits);
+    let p2 = scalar_mul(cs, &public_key, &u2_bits);
+    let point = add(cs, &p1, &p2);
+
+    let x = reduce(cs, &point.x, &base_modulus());
+    let x = reduce(cs, &x, &order);
+    assert_equal(cs, &x, &r);
+}
+
+// The verification emits over three million constraints, which takes about half a minute in
+// debug builds, so the tests only run with the `slow-tests` feature.
+#[cfg(all(test, feature = "slow-tests"))]
+mod tests {
+    use super::*;
+    use crate::gadgets::Evaluator;
+    use crate::utils::secp256k1::secp256k1::ecdsa_operands;
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    fn verify_with_evaluator(signer: &SigningKey, public_key: &SigningKey) {
+        let prehash = [7u8; 32];
+        let signature: Signature = signer.sign_prehash(&prehash).unwrap();
+        let operands: Vec<Signal> =
+            ecdsa_operands(public_key.verifying_key(), &prehash, &signature)
+                .into_iter()
+                .map(Signal::from_value)
+                .collect();
+
+        let limbs: Vec<&[Signal]> = operands.chunks(4).collect();
+        ecdsa_verify(
+            &mut Evaluator,
+            limbs[0],
+            limbs[1],
+            limbs[2],
+            (limbs[3], limbs[4]),
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_accepts_k256_signature() {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        verify_with_evaluator(&key, &key);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsatisfiable constraint")]
+    fn test_ecdsa_rejects_signature_from_another_key() {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let other = SigningKey::random(&mut rand::thread_rng());
+        verify_with_evaluator(&other, &key);
+    }
+}
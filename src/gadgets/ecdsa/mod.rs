@@ -0,0 +1,2 @@
+mod ecdsa;
+pub use self::ecdsa::ecdsa_verify;
@@ -0,0 +1,60 @@
+use crate::gadgets::babyjubjub::{
+    add, assert_on_curve, clear_cofactor, fixed_base_mul, scalar_mul, PointSignal,
+};
+use crate::gadgets::boolean::{assert_at_most, to_bits, to_bits_strict};
+use crate::gadgets::{poseidon, ConstraintSystem, Signal};
+use crate::utils::babyjubjub::babyjubjub::{Point, Scalar, SCALAR_BITS};
+use crate::utils::field::field::FieldElement;
+use ark_ff::{BigInteger, Field, PrimeField};
+
+/// Verifies an EdDSA-Poseidon signature `(R8, S)` on `message` under `public_key`, enforcing
+/// that both points lie on the curve, that `S` is reduced modulo the subgroup order, that
+/// `8 * A` is not the identity, and circomlib's equation `S * B8 = R8 + h * (8 * A)` with
+/// `h = Poseidon(R8, A, message)`.
+pub fn eddsa_verify<CS: ConstraintSystem>(
+    cs: &mut CS,
+    public_key: &PointSignal,
+    r8: &PointSignal,
+    s: &Signal,
+    message: &Signal,
+) {
+    assert_on_curve(cs, public_key);
+    assert_on_curve(cs, r8);
+
+    // Points of small order become the identity, whose x-coordinate is zero.
+    let a8 = clear_cofactor(cs, public_key);
+    assert_non_zero(cs, &a8.x);
+
+    let s_bits = to_bits(cs, s, SCALAR_BITS);
+    let mut order_minus_one = Scalar::MODULUS;
+    order_minus_one.sub_with_borrow(&1u64.into());
+    assert_at_most(cs, &s_bits, &order_minus_one.to_bits_le()[..SCALAR_BITS]);
+
+    let challenge = poseidon(
+        cs,
+        &[
+            r8.x.clone(),
+            r8.y.clone(),
+            public_key.x.clone(),
+            public_key.y.clone(),
+            message.clone(),
+        ],
+    );
+    let challenge_bits = to_bits_strict(cs, &challenge);
+
+    let left = fixed_base_mul(cs, &Point::base8(), &s_bits);
+    let h_times_a8 = scalar_mul(cs, &a8, &challenge_bits);
+    let right = add(cs, r8, &h_times_a8);
+    cs.assert_equal(&left.x, &right.x);
+    cs.assert_equal(&left.y, &right.y);
+}
+
+/// Enforces `value != 0` by witnessing its inverse, one constraint.
+fn assert_non_zero<CS: ConstraintSystem>(cs: &mut CS, value: &Signal) {
+    let inverse = cs.allocate(
+        value
+            .value
+            .map(|value| value.inverse().unwrap_or(FieldElement::from(0))),
+    );
+    cs.enforce(value, &inverse, &Signal::one());
+}
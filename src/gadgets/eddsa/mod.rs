@@ -0,0 +1,2 @@
+mod eddsa;
+pub use self::eddsa::eddsa_verify;
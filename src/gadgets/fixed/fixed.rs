@@ -0,0 +1,103 @@
+use crate::gadgets::boolean::{not, to_bits};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::{from_signed, power_of_two, to_signed, MODULUS_BITS};
+use crate::utils::fixed::fixed::FIXED_POINT_BITS;
+
+/// `floor(value / 2^shift)` for a signed `value` whose quotient fits in `FIXED_POINT_BITS`,
+/// with the remainder and quotient range checked so the split is unique.
+pub fn truncate<CS: ConstraintSystem>(cs: &mut CS, value: &Signal, shift: usize) -> Signal {
+    assert!(
+        FIXED_POINT_BITS + shift < MODULUS_BITS,
+        "Cannot truncate {} bits without wrapping around the field",
+        shift
+    );
+
+    let split = value.value.map(|value| {
+        let value = to_signed(&value);
+        let quotient = &value >> shift;
+        let remainder = &value - (&quotient << shift);
+        (from_signed(&quotient), from_signed(&remainder))
+    });
+
+    let remainder = cs.allocate(split.map(|(_, remainder)| remainder));
+    to_bits(cs, &remainder, shift);
+
+    // The quotient is allocated shifted by 2^(FIXED_POINT_BITS - 1) to keep it non-negative.
+    let offset = power_of_two(FIXED_POINT_BITS - 1);
+    let shifted = cs.allocate(split.map(|(quotient, _)| quotient + offset));
+    to_bits(cs, &shifted, FIXED_POINT_BITS);
+    let quotient = &shifted - &Signal::constant(offset);
+
+    cs.assert_equal(&(&(&quotient * power_of_two(shift)) + &remainder), value);
+    quotient
+}
+
+/// Product of two fixed-point values with `scale` fractional bits, rounded down.
+pub fn fixed_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    a: &Signal,
+    b: &Signal,
+    scale: usize,
+) -> Signal {
+    let product = cs.multiply(a, b);
+    truncate(cs, &product, scale)
+}
+
+/// 1 when `a < b` and 0 otherwise, for signed operands of `FIXED_POINT_BITS` bits, which are
+/// range checked.
+pub fn less_than<CS: ConstraintSystem>(cs: &mut CS, a: &Signal, b: &Signal) -> Signal {
+    assert_signed(cs, a);
+    assert_signed(cs, b);
+
+    // a - b + 2^FIXED_POINT_BITS is below 2^FIXED_POINT_BITS exactly when a < b.
+    let shifted = &(a - b) + &Signal::constant(power_of_two(FIXED_POINT_BITS));
+    let bits = to_bits(cs, &shifted, FIXED_POINT_BITS + 1);
+    not(&bits[FIXED_POINT_BITS])
+}
+
+/// Enforces `-2^(FIXED_POINT_BITS - 1) <= value < 2^(FIXED_POINT_BITS - 1)`.
+fn assert_signed<CS: ConstraintSystem>(cs: &mut CS, value: &Signal) {
+    let offset = power_of_two(FIXED_POINT_BITS - 1);
+    to_bits(cs, &(value + &Signal::constant(offset)), FIXED_POINT_BITS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::Evaluator;
+    use crate::utils::field::field::FieldElement;
+    use crate::utils::fixed::fixed::{decode, encode};
+
+    fn fixed(literal: &str) -> Signal {
+        Signal::from_value(encode(literal, 16).unwrap())
+    }
+
+    #[test]
+    fn test_multiplication_rounds_down() {
+        let mut cs = Evaluator;
+        let product = fixed_mul(&mut cs, &fixed("19.99"), &fixed("2.5"), 16);
+        assert_eq!(decode(&product.value.unwrap(), 16), "49.9749755859375");
+
+        // 0.1 encodes as 6553, and -98304 * 6553 / 2^16 = -9829.5 rounds to -9830.
+        let product = fixed_mul(&mut cs, &fixed("-1.5"), &fixed("0.1"), 16);
+        assert_eq!(product.value.unwrap(), encode("-9830", 0).unwrap());
+    }
+
+    #[test]
+    fn test_less_than_on_signed_values() {
+        let mut cs = Evaluator;
+        let compare = |cs: &mut Evaluator, a, b| less_than(cs, &fixed(a), &fixed(b)).value.unwrap();
+
+        assert_eq!(compare(&mut cs, "1.25", "1.5"), FieldElement::from(1));
+        assert_eq!(compare(&mut cs, "1.5", "1.5"), FieldElement::from(0));
+        assert_eq!(compare(&mut cs, "-3", "-2.75"), FieldElement::from(1));
+        assert_eq!(compare(&mut cs, "2", "-2"), FieldElement::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsatisfiable constraint")]
+    fn test_less_than_rejects_out_of_range_operands() {
+        let too_large = Signal::from_value(power_of_two(FIXED_POINT_BITS - 1));
+        less_than(&mut Evaluator, &too_large, &fixed("0"));
+    }
+}
@@ -0,0 +1,2 @@
+mod fixed;
+pub use self::fixed::*;
@@ -0,0 +1,95 @@
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::FieldElement;
+
+/// Proves that `inputs` are the first columns of a row of `table` and returns the next column
+/// of that row, or 1 when the inputs fill whole rows.
+///
+/// The row is selected by one boolean per row, exactly one of which is set, so every column is
+/// a linear combination of the selectors: `rows + 1 + columns` constraints in all.
+pub fn lookup<CS: ConstraintSystem>(
+    cs: &mut CS,
+    table: &[Vec<FieldElement>],
+    inputs: &[Signal],
+) -> Signal {
+    let width = inputs.len();
+    let columns = table.first().map_or(width, Vec::len);
+    if (columns != width && columns != width + 1) || table.iter().any(|row| row.len() != columns) {
+        panic!(
+            "Lookup tables with {} operands need rows of {} or {} columns",
+            width,
+            width,
+            width + 1
+        );
+    }
+
+    // Inputs missing from the table select no row, which leaves the constraints unsatisfied.
+    let values: Option<Vec<FieldElement>> = inputs.iter().map(|input| input.value).collect();
+    let selected = values.map(|values| table.iter().position(|row| row[..width] == values[..]));
+
+    let selectors: Vec<Signal> = (0..table.len())
+        .map(|row| {
+            let selector =
+                cs.allocate(selected.map(|selected| FieldElement::from(selected == Some(row))));
+            cs.assert_boolean(&selector);
+            selector
+        })
+        .collect();
+    let column = |column: usize| {
+        table
+            .iter()
+            .zip(&selectors)
+            .fold(Signal::zero(), |sum, (row, selector)| {
+                &sum + &(selector * row[column])
+            })
+    };
+
+    let count = selectors
+        .iter()
+        .fold(Signal::zero(), |sum, selector| &sum + selector);
+    cs.assert_equal(&count, &Signal::one());
+    for (i, input) in inputs.iter().enumerate() {
+        cs.assert_equal(&column(i), input);
+    }
+    if columns > width {
+        column(width)
+    } else {
+        Signal::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{CostCounter, Evaluator};
+
+    fn table(rows: &[&[i64]]) -> Vec<Vec<FieldElement>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| FieldElement::from(x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_lookup_resolves_row() {
+        let squares = table(&[&[0, 0], &[1, 1], &[2, 4], &[3, 9]]);
+        let input = Signal::from_value(FieldElement::from(3));
+
+        let square = lookup(&mut Evaluator, &squares, &[input]);
+        assert_eq!(square.value, Some(FieldElement::from(9)));
+
+        let mut counter = CostCounter::default();
+        lookup(&mut counter, &squares, &[Signal::default()]);
+        assert_eq!(counter.variables, 4);
+        assert_eq!(counter.constraints, 4 + 1 + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsatisfiable constraint")]
+    fn test_lookup_rejects_missing_tuple() {
+        let primes = table(&[&[2], &[3], &[5], &[7]]);
+        lookup(
+            &mut Evaluator,
+            &primes,
+            &[Signal::from_value(FieldElement::from(4))],
+        );
+    }
+}
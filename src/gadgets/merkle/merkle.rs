@@ -0,0 +1,26 @@
+use crate::circuits::Operation;
+use crate::gadgets::boolean::select;
+use crate::gadgets::{ConstraintSystem, Signal};
+
+/// Recomputes the root from `leaf`, the sibling `path` and the `directions` bits (1 when the
+/// node is a right child), hashing every level with the two-input `hash` operation, and
+/// constrains it to equal `root`. Returns `None` when `hash` is not implemented by a gadget.
+pub fn merkle_membership<CS: ConstraintSystem>(
+    cs: &mut CS,
+    hash: &Operation,
+    leaf: &Signal,
+    root: &Signal,
+    path: &[Signal],
+    directions: &[Signal],
+) -> Option<Signal> {
+    let mut node = leaf.clone();
+    for (sibling, direction) in path.iter().zip(directions) {
+        // The sibling goes on the left of a right child, the other operand on the right.
+        let left = select(cs, direction, sibling, &node);
+        let right = &(sibling + &node) - &left;
+        node = hash.synthesize(cs, &[left, right])?;
+    }
+
+    cs.assert_equal(&node, root);
+    Some(node)
+}
@@ -0,0 +1,2 @@
+mod merkle;
+pub use self::merkle::merkle_membership;
@@ -0,0 +1,41 @@
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::mimc::mimc::{feistel_constants, round_constants};
+
+/// In-circuit MiMC-7, four multiplication constraints per round.
+pub fn mimc7<CS: ConstraintSystem>(cs: &mut CS, x: &Signal, key: &Signal, rounds: usize) -> Signal {
+    let mut state = x.clone();
+    for constant in round_constants(rounds) {
+        let base = &(&state + key) + &Signal::constant(constant);
+        let square = cs.multiply(&base, &base);
+        let fourth = cs.multiply(&square, &square);
+        let sixth = cs.multiply(&fourth, &square);
+        state = cs.multiply(&sixth, &base);
+    }
+    &state + key
+}
+
+/// In-circuit MiMC Feistel permutation, three multiplication constraints per round.
+pub fn mimc_feistel<CS: ConstraintSystem>(
+    cs: &mut CS,
+    left: &Signal,
+    right: &Signal,
+    key: &Signal,
+    rounds: usize,
+) -> (Signal, Signal) {
+    let (mut left, mut right) = (left.clone(), right.clone());
+    for (round, constant) in feistel_constants(rounds).into_iter().enumerate() {
+        let base = &(&left + key) + &Signal::constant(constant);
+        let square = cs.multiply(&base, &base);
+        let fourth = cs.multiply(&square, &square);
+        let fifth = cs.multiply(&fourth, &base);
+
+        let mixed = &right + &fifth;
+        if round + 1 < rounds {
+            right = left;
+            left = mixed;
+        } else {
+            right = mixed;
+        }
+    }
+    (left, right)
+}
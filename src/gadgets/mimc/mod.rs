@@ -0,0 +1,3 @@
+mod mimc;
+pub use self::mimc::mimc7;
+pub use self::mimc::mimc_feistel;
@@ -0,0 +1,28 @@
+pub mod babyjubjub;
+pub mod boolean;
+mod constraint_system;
+mod ecdsa;
+mod eddsa;
+mod fixed;
+mod lookup;
+mod merkle;
+mod mimc;
+pub mod nonnative;
+mod poseidon;
+pub mod secp256k1;
+mod sha256;
+
+pub use constraint_system::CostCounter;
+pub use constraint_system::ConstraintSystem;
+pub use constraint_system::Signal;
+#[cfg(test)]
+pub use constraint_system::Evaluator;
+pub use ecdsa::ecdsa_verify;
+pub use eddsa::eddsa_verify;
+pub use fixed::{fixed_mul, less_than};
+pub use lookup::lookup;
+pub use merkle::merkle_membership;
+pub use mimc::mimc7;
+pub use mimc::mimc_feistel;
+pub use poseidon::poseidon;
+pub use sha256::sha256;
@@ -0,0 +1,2 @@
+mod nonnative;
+pub use self::nonnative::*;
@@ -0,0 +1,343 @@
+use crate::gadgets::boolean::{assert_at_most, to_bits};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::{from_signed, power_of_two, to_signed, FieldElement};
+use crate::utils::secp256k1::secp256k1::{to_limbs, LIMBS, LIMB_BITS};
+use num_bigint::{BigInt, BigUint};
+
+/// Integer below `2^256` held as little-endian limbs of `LIMB_BITS`, standing for its residue
+/// modulo whichever modulus it is operated on with. Limbs are range checked when allocated but
+/// the integer is only reduced on request.
+#[derive(Debug, Clone)]
+pub struct NonNative {
+    pub limbs: Vec<Signal>,
+}
+
+impl NonNative {
+    pub fn constant(value: &BigUint) -> Self {
+        NonNative {
+            limbs: to_limbs(value).into_iter().map(Signal::constant).collect(),
+        }
+    }
+
+    pub fn allocate<CS: ConstraintSystem>(cs: &mut CS, value: Option<BigUint>) -> Self {
+        let limbs = value.map(|value| to_limbs(&value));
+        let limbs: Vec<Signal> = (0..LIMBS)
+            .map(|i| cs.allocate(limbs.as_ref().map(|limbs| limbs[i])))
+            .collect();
+        NonNative::from_limbs(cs, &limbs)
+    }
+
+    /// Wraps limbs computed elsewhere, range checking them.
+    pub fn from_limbs<CS: ConstraintSystem>(cs: &mut CS, limbs: &[Signal]) -> Self {
+        let value = NonNative {
+            limbs: limbs.to_vec(),
+        };
+        value.to_bits(cs);
+        value
+    }
+
+    /// Little-endian bits of the integer, range checking every limb.
+    pub fn to_bits<CS: ConstraintSystem>(&self, cs: &mut CS) -> Vec<Signal> {
+        let mut bits = Vec::with_capacity(LIMBS * LIMB_BITS);
+        for limb in &self.limbs {
+            bits.extend(to_bits(cs, limb, LIMB_BITS));
+        }
+        bits
+    }
+
+    pub fn value(&self) -> Option<BigUint> {
+        self.limbs
+            .iter()
+            .rev()
+            .try_fold(BigUint::default(), |acc, limb| {
+                limb.value
+                    .map(|limb| (acc << LIMB_BITS) + BigUint::from(limb))
+            })
+    }
+}
+
+/// Integer `sum(coefficients[i] * 2^(LIMB_BITS * i))` whose coefficients may be negative or
+/// exceed a limb, as long as their absolute values stay below `2^bits`. Products of
+/// `NonNative`s are built as polynomials and checked against zero in one pass.
+#[derive(Debug, Clone)]
+pub struct Poly {
+    coefficients: Vec<Signal>,
+    bits: usize,
+}
+
+impl From<&NonNative> for Poly {
+    fn from(value: &NonNative) -> Self {
+        Poly {
+            coefficients: value.limbs.clone(),
+            bits: LIMB_BITS,
+        }
+    }
+}
+
+impl Poly {
+    pub fn constant(value: &BigUint) -> Self {
+        Poly {
+            coefficients: value
+                .to_u64_digits()
+                .into_iter()
+                .map(|digit| Signal::constant(FieldElement::from(digit)))
+                .collect(),
+            bits: LIMB_BITS,
+        }
+    }
+
+    /// Schoolbook product, one constraint per pair of limbs.
+    pub fn product<CS: ConstraintSystem>(cs: &mut CS, a: &NonNative, b: &NonNative) -> Self {
+        let mut coefficients = vec![Signal::zero(); a.limbs.len() + b.limbs.len() - 1];
+        for (i, x) in a.limbs.iter().enumerate() {
+            for (j, y) in b.limbs.iter().enumerate() {
+                coefficients[i + j] = &coefficients[i + j] + &cs.multiply(x, y);
+            }
+        }
+        Poly {
+            coefficients,
+            bits: 2 * LIMB_BITS + log2_ceil(a.limbs.len().min(b.limbs.len())),
+        }
+    }
+
+    /// Product with a constant, which stays linear.
+    pub fn product_with_constant(a: &[Signal], constant: &BigUint) -> Self {
+        let digits = constant.to_u64_digits();
+        let mut coefficients = vec![Signal::zero(); a.len() + digits.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, &digit) in digits.iter().enumerate() {
+                coefficients[i + j] = &coefficients[i + j] + &(x * FieldElement::from(digit));
+            }
+        }
+        Poly {
+            coefficients,
+            bits: 2 * LIMB_BITS + log2_ceil(a.len().min(digits.len())),
+        }
+    }
+
+    pub fn scale(&self, factor: u64) -> Self {
+        Poly {
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|c| c * FieldElement::from(factor))
+                .collect(),
+            bits: self.bits + (u64::BITS - factor.leading_zeros()) as usize,
+        }
+    }
+
+    pub fn add(&self, other: &Poly) -> Self {
+        let length = self.coefficients.len().max(other.coefficients.len());
+        let zero = Signal::zero();
+        Poly {
+            coefficients: (0..length)
+                .map(|i| {
+                    let a = self.coefficients.get(i).unwrap_or(&zero);
+                    let b = other.coefficients.get(i).unwrap_or(&zero);
+                    a + b
+                })
+                .collect(),
+            bits: self.bits.max(other.bits) + 1,
+        }
+    }
+
+    pub fn sub(&self, other: &Poly) -> Self {
+        self.add(&other.negate())
+    }
+
+    fn negate(&self) -> Self {
+        Poly {
+            coefficients: self.coefficients.iter().map(|c| -c).collect(),
+            bits: self.bits,
+        }
+    }
+
+    pub fn value(&self) -> Option<BigInt> {
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(BigInt::default(), |acc, c| {
+                c.value.map(|c| (acc << LIMB_BITS) + to_signed(&c))
+            })
+    }
+}
+
+fn log2_ceil(n: usize) -> usize {
+    (usize::BITS - (n - 1).leading_zeros()) as usize
+}
+
+/// Allocates `value + 2^offset_bits`, range checked to `offset_bits + 1` bits, and returns the
+/// signal of the signed `value`.
+fn allocate_signed<CS: ConstraintSystem>(
+    cs: &mut CS,
+    value: Option<BigInt>,
+    offset_bits: usize,
+) -> Signal {
+    let offset = power_of_two(offset_bits);
+    let shifted = cs.allocate(value.map(|value| from_signed(&value) + offset));
+    to_bits(cs, &shifted, offset_bits + 1);
+    &shifted - &Signal::constant(offset)
+}
+
+/// Enforces that the polynomial evaluates to zero as an integer by propagating carries from
+/// the least significant coefficient, one linear constraint and a range check per carry.
+pub fn assert_zero<CS: ConstraintSystem>(cs: &mut CS, poly: &Poly) {
+    let carry_bits = (poly.bits + 1).saturating_sub(LIMB_BITS);
+    let shift = power_of_two(LIMB_BITS);
+    let (last, coefficients) = poly.coefficients.split_last().unwrap();
+
+    let mut carry = Signal::zero();
+    let mut carry_value = Some(BigInt::default());
+    for coefficient in coefficients {
+        let total = coefficient + &carry;
+        carry_value = carry_value
+            .zip(coefficient.value)
+            .map(|(carry, c)| (carry + to_signed(&c)) >> LIMB_BITS);
+        carry = allocate_signed(cs, carry_value.clone(), carry_bits);
+        cs.assert_equal(&total, &(&carry * shift));
+    }
+    cs.assert_equal(&(last + &carry), &Signal::zero());
+}
+
+/// Enforces that the polynomial is a multiple of `modulus`, given the quotient as a hint.
+pub fn assert_zero_mod<CS: ConstraintSystem>(cs: &mut CS, poly: &Poly, modulus: &BigUint) {
+    let value_bits = poly.bits + LIMB_BITS * (poly.coefficients.len() - 1) + 1;
+    let quotient_bits = value_bits + 1 - modulus.bits() as usize;
+    let quotient = poly
+        .value()
+        .map(|value| value / BigInt::from(modulus.clone()));
+
+    // The quotient is allocated shifted by 2^quotient_bits to keep it non-negative.
+    let offset = BigUint::from(1u32) << quotient_bits;
+    let shifted = quotient.map(|quotient| (quotient + BigInt::from(offset.clone())).into_parts().1);
+    let mut digits = shifted.map(|shifted| shifted.to_u64_digits());
+    let mut limbs = Vec::new();
+    let mut remaining = quotient_bits + 1;
+    while remaining > 0 {
+        let width = remaining.min(LIMB_BITS);
+        let digit = digits.as_mut().map(|digits| {
+            let digit = if digits.is_empty() {
+                0
+            } else {
+                digits.remove(0)
+            };
+            FieldElement::from(digit)
+        });
+        let limb = cs.allocate(digit);
+        to_bits(cs, &limb, width);
+        limbs.push(limb);
+        remaining -= width;
+    }
+
+    let multiple = Poly::product_with_constant(&limbs, modulus);
+    let poly = poly
+        .sub(&multiple)
+        .add(&Poly::constant(&(offset * modulus)));
+    assert_zero(cs, &poly);
+}
+
+/// Residue of `value` modulo `modulus`, as a non-negative integer.
+pub fn modulo(value: &BigInt, modulus: &BigUint) -> BigUint {
+    let modulus = BigInt::from(modulus.clone());
+    (((value % &modulus) + &modulus) % &modulus).into_parts().1
+}
+
+/// `a * b mod modulus`.
+pub fn mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    a: &NonNative,
+    b: &NonNative,
+    modulus: &BigUint,
+) -> NonNative {
+    let value = a.value().zip(b.value()).map(|(a, b)| a * b % modulus);
+    let result = NonNative::allocate(cs, value);
+    let poly = Poly::product(cs, a, b).sub(&Poly::from(&result));
+    assert_zero_mod(cs, &poly, modulus);
+    result
+}
+
+/// Inverse of `a` modulo the prime `modulus`, unsatisfiable when `a` is a multiple of it.
+pub fn inverse<CS: ConstraintSystem>(cs: &mut CS, a: &NonNative, modulus: &BigUint) -> NonNative {
+    let value = a.value().map(|a| a.modpow(&(modulus - 2u32), modulus));
+    let result = NonNative::allocate(cs, value);
+    let poly = Poly::product(cs, a, &result).sub(&Poly::constant(&BigUint::from(1u32)));
+    assert_zero_mod(cs, &poly, modulus);
+    result
+}
+
+/// Enforces `a < modulus`.
+pub fn assert_canonical<CS: ConstraintSystem>(cs: &mut CS, a: &NonNative, modulus: &BigUint) {
+    let bits = a.to_bits(cs);
+    let bound = modulus - 1u32;
+    let bound: Vec<bool> = (0..bits.len()).map(|i| bound.bit(i as u64)).collect();
+    assert_at_most(cs, &bits, &bound);
+}
+
+/// Canonical representative of `a` modulo `modulus`.
+pub fn reduce<CS: ConstraintSystem>(cs: &mut CS, a: &NonNative, modulus: &BigUint) -> NonNative {
+    let result = NonNative::allocate(cs, a.value().map(|a| a % modulus));
+    assert_canonical(cs, &result, modulus);
+    assert_zero_mod(cs, &Poly::from(a).sub(&Poly::from(&result)), modulus);
+    result
+}
+
+/// `a` when the boolean `bit` is 1 and `b` otherwise, one constraint per limb.
+pub fn select<CS: ConstraintSystem>(
+    cs: &mut CS,
+    bit: &Signal,
+    a: &NonNative,
+    b: &NonNative,
+) -> NonNative {
+    NonNative {
+        limbs: a
+            .limbs
+            .iter()
+            .zip(&b.limbs)
+            .map(|(a, b)| b + &cs.multiply(bit, &(a - b)))
+            .collect(),
+    }
+}
+
+/// Enforces limb-wise equality, which only implies congruence for canonical operands.
+pub fn assert_equal<CS: ConstraintSystem>(cs: &mut CS, a: &NonNative, b: &NonNative) {
+    for (a, b) in a.limbs.iter().zip(&b.limbs) {
+        cs.assert_equal(a, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::Evaluator;
+    use crate::utils::secp256k1::secp256k1::{base_modulus, group_order};
+
+    #[test]
+    fn test_modular_arithmetic_matches_big_integers() {
+        let mut cs = Evaluator;
+        let modulus = base_modulus();
+        let a_value = &modulus - 5u32;
+        let b_value = (BigUint::from(1u32) << 255usize) + 12345u32;
+        let a = NonNative::allocate(&mut cs, Some(a_value.clone()));
+        let b = NonNative::allocate(&mut cs, Some(b_value.clone()));
+
+        let product = mul(&mut cs, &a, &b, &modulus);
+        assert_eq!(product.value(), Some(&a_value * &b_value % &modulus));
+
+        let a_inverse = inverse(&mut cs, &a, &modulus);
+        let one = mul(&mut cs, &a, &a_inverse, &modulus);
+        assert_eq!(one.value(), Some(BigUint::from(1u32)));
+
+        let reduced = reduce(&mut cs, &b, &group_order());
+        assert_eq!(reduced.value(), Some(&b_value % group_order()));
+        assert_canonical(&mut cs, &reduced, &group_order());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_zero_mod_rejects_non_multiples() {
+        let mut cs = Evaluator;
+        let modulus = base_modulus();
+        let a = NonNative::allocate(&mut cs, Some(&modulus + 1u32));
+        assert_zero_mod(&mut cs, &Poly::from(&a), &modulus);
+    }
+}
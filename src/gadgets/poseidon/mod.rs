@@ -0,0 +1,2 @@
+mod poseidon;
+pub use self::poseidon::poseidon;
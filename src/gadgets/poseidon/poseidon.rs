@@ -0,0 +1,62 @@
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::poseidon::poseidon::parameters;
+
+/// In-circuit counterpart of `poseidon_hash`: each S-box costs three multiplication constraints,
+/// the round constants and the MDS layer stay linear.
+pub fn poseidon<CS: ConstraintSystem>(cs: &mut CS, inputs: &[Signal]) -> Signal {
+    let parameters = parameters(inputs.len() + 1);
+    let width = parameters.width;
+
+    let mut state = vec![Signal::zero()];
+    state.extend_from_slice(inputs);
+
+    for round in 0..parameters.rounds() {
+        for (element, &constant) in state
+            .iter_mut()
+            .zip(&parameters.round_constants[round * width..])
+        {
+            *element = &*element + &Signal::constant(constant);
+        }
+
+        let sbox_count = if parameters.is_full_round(round) {
+            width
+        } else {
+            1
+        };
+        for element in &mut state[..sbox_count] {
+            let square = cs.multiply(element, element);
+            let fourth = cs.multiply(&square, &square);
+            *element = cs.multiply(&fourth, element);
+        }
+
+        state = parameters
+            .mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&state)
+                    .fold(Signal::zero(), |sum, (&m, s)| &sum + &(s * m))
+            })
+            .collect();
+    }
+
+    state.swap_remove(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::CostCounter;
+    use crate::utils::poseidon::poseidon::FULL_ROUNDS;
+
+    #[test]
+    fn test_poseidon_constraint_count() {
+        let mut counter = CostCounter::default();
+        poseidon(&mut counter, &[Signal::default(), Signal::default()]);
+
+        let parameters = parameters(3);
+        let sboxes = FULL_ROUNDS * 3 + parameters.partial_rounds;
+        assert_eq!(counter.variables, 3 * sboxes);
+        assert_eq!(counter.constraints, 3 * sboxes);
+    }
+}
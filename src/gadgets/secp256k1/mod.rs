@@ -0,0 +1,2 @@
+mod secp256k1;
+pub use self::secp256k1::*;
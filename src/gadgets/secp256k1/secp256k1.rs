@@ -0,0 +1,291 @@
+use crate::gadgets::nonnative::{
+    assert_zero_mod, modulo, mul, select as select_limbs, NonNative, Poly,
+};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::secp256k1::secp256k1::{base_modulus, coordinates, nothing_up_my_sleeve_point};
+use k256::{AffinePoint, ProjectivePoint};
+use num_bigint::{BigInt, BigUint};
+
+/// Affine secp256k1 point with non-native coordinates. The identity has no affine form, so
+/// scalar multiplications start from an offset point and subtract it at the end.
+#[derive(Debug, Clone)]
+pub struct PointSignal {
+    pub x: NonNative,
+    pub y: NonNative,
+}
+
+impl PointSignal {
+    pub fn constant(point: &AffinePoint) -> Self {
+        let (x, y) = coordinates(point);
+        PointSignal {
+            x: NonNative::constant(&x),
+            y: NonNative::constant(&y),
+        }
+    }
+}
+
+/// Coordinate of the second operand of an addition, whose products with the slope stay linear
+/// when it is constant.
+enum Coordinate<'a> {
+    Variable(&'a NonNative),
+    Constant(BigUint),
+}
+
+impl Coordinate<'_> {
+    fn times<CS: ConstraintSystem>(&self, cs: &mut CS, a: &NonNative) -> Poly {
+        match self {
+            Coordinate::Variable(b) => Poly::product(cs, a, b),
+            Coordinate::Constant(b) => Poly::product_with_constant(&a.limbs, b),
+        }
+    }
+
+    fn poly(&self) -> Poly {
+        match self {
+            Coordinate::Variable(b) => Poly::from(*b),
+            Coordinate::Constant(b) => Poly::constant(b),
+        }
+    }
+
+    fn value(&self) -> Option<BigInt> {
+        match self {
+            Coordinate::Variable(b) => b.value().map(BigInt::from),
+            Coordinate::Constant(b) => Some(BigInt::from(b.clone())),
+        }
+    }
+}
+
+/// Enforces `y^2 = x^3 + 7`.
+pub fn assert_on_curve<CS: ConstraintSystem>(cs: &mut CS, point: &PointSignal) {
+    let modulus = base_modulus();
+    let square = mul(cs, &point.x, &point.x, &modulus);
+    let poly = Poly::product(cs, &point.y, &point.y)
+        .sub(&Poly::product(cs, &square, &point.x))
+        .sub(&Poly::constant(&BigUint::from(7u32)));
+    assert_zero_mod(cs, &poly, &modulus);
+}
+
+/// Third point on the line of slope `lambda` through `(x1, y1)` and a point of x-coordinate
+/// `x2`, reflected: `x3 = lambda^2 - x1 - x2` and `y3 = lambda (x1 - x3) - y1`.
+fn finish_chord<CS: ConstraintSystem>(
+    cs: &mut CS,
+    lambda: &NonNative,
+    p: &PointSignal,
+    x2: &Coordinate,
+) -> PointSignal {
+    let modulus = base_modulus();
+    let lambda_value = lambda.value().map(BigInt::from);
+
+    let x3_value = lambda_value
+        .clone()
+        .zip(p.x.value())
+        .zip(x2.value())
+        .map(|((lambda, x1), x2)| modulo(&(&lambda * &lambda - BigInt::from(x1) - x2), &modulus));
+    let x = NonNative::allocate(cs, x3_value);
+    let poly = Poly::product(cs, lambda, lambda)
+        .sub(&Poly::from(&p.x))
+        .sub(&x2.poly())
+        .sub(&Poly::from(&x));
+    assert_zero_mod(cs, &poly, &modulus);
+
+    let y3_value = lambda_value
+        .zip(p.x.value())
+        .zip(x.value())
+        .zip(p.y.value())
+        .map(|(((lambda, x1), x3), y1)| {
+            modulo(
+                &(lambda * (BigInt::from(x1) - BigInt::from(x3)) - BigInt::from(y1)),
+                &modulus,
+            )
+        });
+    let y = NonNative::allocate(cs, y3_value);
+    let poly = Poly::product(cs, lambda, &p.x)
+        .sub(&Poly::product(cs, lambda, &x))
+        .sub(&Poly::from(&p.y))
+        .sub(&Poly::from(&y));
+    assert_zero_mod(cs, &poly, &modulus);
+
+    PointSignal { x, y }
+}
+
+fn add_coordinates<CS: ConstraintSystem>(
+    cs: &mut CS,
+    p: &PointSignal,
+    x2: Coordinate,
+    y2: Coordinate,
+) -> PointSignal {
+    let modulus = base_modulus();
+
+    // The chord is only defined for distinct x-coordinates, which the inverse witnesses.
+    let dx = x2
+        .value()
+        .zip(p.x.value())
+        .map(|(x2, x1)| modulo(&(x2 - BigInt::from(x1)), &modulus));
+    let dx_inverse = NonNative::allocate(cs, dx.map(|dx| dx.modpow(&(&modulus - 2u32), &modulus)));
+    let poly = x2
+        .times(cs, &dx_inverse)
+        .sub(&Poly::product(cs, &dx_inverse, &p.x))
+        .sub(&Poly::constant(&BigUint::from(1u32)));
+    assert_zero_mod(cs, &poly, &modulus);
+
+    let lambda_value =
+        y2.value()
+            .zip(p.y.value())
+            .zip(dx_inverse.value())
+            .map(|((y2, y1), inverse)| {
+                modulo(&((y2 - BigInt::from(y1)) * BigInt::from(inverse)), &modulus)
+            });
+    let lambda = NonNative::allocate(cs, lambda_value);
+    let poly = x2
+        .times(cs, &lambda)
+        .sub(&Poly::product(cs, &lambda, &p.x))
+        .sub(&y2.poly())
+        .add(&Poly::from(&p.y));
+    assert_zero_mod(cs, &poly, &modulus);
+
+    finish_chord(cs, &lambda, p, &x2)
+}
+
+/// Adds points with distinct x-coordinates, unsatisfiable otherwise.
+pub fn add<CS: ConstraintSystem>(cs: &mut CS, p: &PointSignal, q: &PointSignal) -> PointSignal {
+    add_coordinates(
+        cs,
+        p,
+        Coordinate::Variable(&q.x),
+        Coordinate::Variable(&q.y),
+    )
+}
+
+/// Adds a constant point with an x-coordinate distinct from `p`'s.
+pub fn add_constant<CS: ConstraintSystem>(
+    cs: &mut CS,
+    p: &PointSignal,
+    q: &AffinePoint,
+) -> PointSignal {
+    let (x, y) = coordinates(q);
+    add_coordinates(cs, p, Coordinate::Constant(x), Coordinate::Constant(y))
+}
+
+/// Doubles a point. secp256k1 has no point of order two, so the tangent is always defined.
+pub fn double<CS: ConstraintSystem>(cs: &mut CS, p: &PointSignal) -> PointSignal {
+    let modulus = base_modulus();
+
+    let lambda_value = p.x.value().zip(p.y.value()).map(|(x, y)| {
+        let denominator = (&y * 2u32).modpow(&(&modulus - 2u32), &modulus);
+        &x * &x * 3u32 * denominator % &modulus
+    });
+    let lambda = NonNative::allocate(cs, lambda_value);
+    let poly = Poly::product(cs, &lambda, &p.y)
+        .scale(2)
+        .sub(&Poly::product(cs, &p.x, &p.x).scale(3));
+    assert_zero_mod(cs, &poly, &modulus);
+
+    finish_chord(cs, &lambda, p, &Coordinate::Variable(&p.x))
+}
+
+/// `p` when the boolean `bit` is 1 and `q` otherwise.
+pub fn select<CS: ConstraintSystem>(
+    cs: &mut CS,
+    bit: &Signal,
+    p: &PointSignal,
+    q: &PointSignal,
+) -> PointSignal {
+    PointSignal {
+        x: select_limbs(cs, bit, &p.x, &q.x),
+        y: select_limbs(cs, bit, &p.y, &q.y),
+    }
+}
+
+fn offset() -> ProjectivePoint {
+    nothing_up_my_sleeve_point("zk-cache secp256k1 offset")
+}
+
+/// Multiplies `point` by the integer with the given little-endian boolean bits with
+/// double-and-add. The result must not be the identity.
+pub fn scalar_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    point: &PointSignal,
+    bits: &[Signal],
+) -> PointSignal {
+    let mut acc = PointSignal::constant(&offset().to_affine());
+    for bit in bits.iter().rev() {
+        acc = double(cs, &acc);
+        let sum = add(cs, &acc, point);
+        acc = select(cs, bit, &sum, &acc);
+    }
+
+    // The offset was doubled once per bit.
+    let correction = (0..bits.len()).fold(offset(), |point, _| point.double());
+    add_constant(cs, &acc, &(-correction).to_affine())
+}
+
+/// Multiplies a constant point by the integer with the given little-endian boolean bits,
+/// adding the precomputed `2^i * base` for every set bit. The result must not be the identity.
+pub fn fixed_base_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    base: &ProjectivePoint,
+    bits: &[Signal],
+) -> PointSignal {
+    let mut acc = PointSignal::constant(&offset().to_affine());
+    let mut power = *base;
+    for bit in bits {
+        let sum = add_constant(cs, &acc, &power.to_affine());
+        acc = select(cs, bit, &sum, &acc);
+        power = power.double();
+    }
+    add_constant(cs, &acc, &(-offset()).to_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::boolean::to_bits;
+    use crate::gadgets::Evaluator;
+    use crate::utils::field::field::FieldElement;
+    use k256::Scalar;
+
+    fn allocate(point: &ProjectivePoint) -> PointSignal {
+        let (x, y) = coordinates(&point.to_affine());
+        PointSignal {
+            x: NonNative::allocate(&mut Evaluator, Some(x)),
+            y: NonNative::allocate(&mut Evaluator, Some(y)),
+        }
+    }
+
+    fn assert_point(signal: &PointSignal, expected: &ProjectivePoint) {
+        let (x, y) = coordinates(&expected.to_affine());
+        assert_eq!(signal.x.value(), Some(x));
+        assert_eq!(signal.y.value(), Some(y));
+    }
+
+    #[test]
+    fn test_point_operations_match_k256() {
+        let mut cs = Evaluator;
+        let p = ProjectivePoint::GENERATOR * Scalar::from(1234u64);
+        let q = ProjectivePoint::GENERATOR * Scalar::from(98765u64);
+        let (p_signal, q_signal) = (allocate(&p), allocate(&q));
+
+        assert_point(&add(&mut cs, &p_signal, &q_signal), &(p + q));
+        assert_point(&add_constant(&mut cs, &p_signal, &q.to_affine()), &(p + q));
+        assert_point(&double(&mut cs, &p_signal), &p.double());
+        assert_on_curve(&mut cs, &p_signal);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_rejects_equal_x_coordinates() {
+        let p = allocate(&(ProjectivePoint::GENERATOR * Scalar::from(5u64)));
+        add(&mut Evaluator, &p, &p);
+    }
+
+    #[test]
+    fn test_scalar_multiplication_matches_k256() {
+        let mut cs = Evaluator;
+        let scalar = 0xb5a3u64;
+        let bits = to_bits(&mut cs, &Signal::from_value(FieldElement::from(scalar)), 16);
+        let base = ProjectivePoint::GENERATOR * Scalar::from(3u64);
+        let expected = base * Scalar::from(scalar);
+
+        assert_point(&scalar_mul(&mut cs, &allocate(&base), &bits), &expected);
+        assert_point(&fixed_base_mul(&mut cs, &base, &bits), &expected);
+    }
+}
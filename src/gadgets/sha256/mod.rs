@@ -0,0 +1,2 @@
+mod sha256;
+pub use self::sha256::sha256;
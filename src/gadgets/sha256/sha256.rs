@@ -0,0 +1,168 @@
+use crate::gadgets::boolean::{add_words, constant_bits, rotate_right, shift_right, to_bits, xor};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::power_of_two;
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const INITIAL_HASH: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// A 32-bit word as little-endian bits.
+type Word = Vec<Signal>;
+
+/// In-circuit SHA-256 of a message given as byte signals. Every byte is range checked through
+/// its bit decomposition; the big-endian digest is returned as its most and least significant
+/// 128-bit halves, which the field holds without reduction.
+pub fn sha256<CS: ConstraintSystem>(cs: &mut CS, bytes: &[Signal]) -> [Signal; 2] {
+    let mut message: Vec<Vec<Signal>> = bytes.iter().map(|byte| to_bits(cs, byte, 8)).collect();
+
+    // Padding: a single 1 bit, zeros up to 56 mod 64 bytes, then the bit length.
+    let bit_length = (bytes.len() as u64) * 8;
+    message.push(constant_bits(0x80, 8));
+    while message.len() % 64 != 56 {
+        message.push(constant_bits(0, 8));
+    }
+    for i in (0..8).rev() {
+        message.push(constant_bits((bit_length >> (8 * i)) & 0xff, 8));
+    }
+
+    let mut hash: Vec<Word> = INITIAL_HASH
+        .iter()
+        .map(|&word| constant_bits(word as u64, 32))
+        .collect();
+    for block in message.chunks(64) {
+        let words: Vec<Word> = block
+            .chunks(4)
+            .map(|word_bytes| word_bytes.iter().rev().flatten().cloned().collect())
+            .collect();
+        hash = compress(cs, &hash, words);
+    }
+
+    // Word 0 holds the most significant bits of the digest.
+    let bits: Vec<&Signal> = hash.iter().rev().flatten().collect();
+    let half = |bits: &[&Signal]| {
+        bits.iter()
+            .enumerate()
+            .fold(Signal::zero(), |half, (i, &bit)| {
+                &half + &(bit * power_of_two(i))
+            })
+    };
+    [half(&bits[128..]), half(&bits[..128])]
+}
+
+fn compress<CS: ConstraintSystem>(
+    cs: &mut CS,
+    hash: &[Word],
+    mut schedule: Vec<Word>,
+) -> Vec<Word> {
+    for t in 16..64 {
+        let s0 = xor3(
+            cs,
+            &rotate_right(&schedule[t - 15], 7),
+            &rotate_right(&schedule[t - 15], 18),
+            &shift_right(&schedule[t - 15], 3),
+        );
+        let s1 = xor3(
+            cs,
+            &rotate_right(&schedule[t - 2], 17),
+            &rotate_right(&schedule[t - 2], 19),
+            &shift_right(&schedule[t - 2], 10),
+        );
+        let word = add_words(cs, &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]]);
+        schedule.push(word);
+    }
+
+    let mut state = hash.to_vec();
+    for (t, word) in schedule.iter().enumerate() {
+        let [a, b, c, d, e, f, g, h] = <[Word; 8]>::try_from(state).unwrap();
+
+        let sigma1 = xor3(
+            cs,
+            &rotate_right(&e, 6),
+            &rotate_right(&e, 11),
+            &rotate_right(&e, 25),
+        );
+        let choice = choose(cs, &e, &f, &g);
+        let sigma0 = xor3(
+            cs,
+            &rotate_right(&a, 2),
+            &rotate_right(&a, 13),
+            &rotate_right(&a, 22),
+        );
+        let majority = majority(cs, &a, &b, &c);
+        let constant = constant_bits(ROUND_CONSTANTS[t] as u64, 32);
+
+        let new_e = add_words(cs, &[&d, &h, &sigma1, &choice, &constant, word]);
+        let new_a = add_words(
+            cs,
+            &[&h, &sigma1, &choice, &constant, word, &sigma0, &majority],
+        );
+        state = vec![new_a, a, b, c, new_e, e, f, g];
+    }
+
+    hash.iter()
+        .zip(&state)
+        .map(|(previous, current)| add_words(cs, &[previous, current]))
+        .collect()
+}
+
+fn xor3<CS: ConstraintSystem>(cs: &mut CS, a: &[Signal], b: &[Signal], c: &[Signal]) -> Word {
+    (0..a.len())
+        .map(|i| {
+            let ab = xor(cs, &a[i], &b[i]);
+            xor(cs, &ab, &c[i])
+        })
+        .collect()
+}
+
+/// `Ch(e, f, g) = g + e * (f - g)`, one constraint per bit.
+fn choose<CS: ConstraintSystem>(cs: &mut CS, e: &[Signal], f: &[Signal], g: &[Signal]) -> Word {
+    (0..e.len())
+        .map(|i| {
+            let selected = cs.multiply(&e[i], &(&f[i] - &g[i]));
+            &g[i] + &selected
+        })
+        .collect()
+}
+
+/// `Maj(a, b, c) = bc + a * (b + c - 2bc)`, two constraints per bit.
+fn majority<CS: ConstraintSystem>(cs: &mut CS, a: &[Signal], b: &[Signal], c: &[Signal]) -> Word {
+    (0..a.len())
+        .map(|i| {
+            let bc = cs.multiply(&b[i], &c[i]);
+            let rest = &(&b[i] + &c[i]) - &(&bc * 2u64.into());
+            let selected = cs.multiply(&a[i], &rest);
+            &bc + &selected
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::CostCounter;
+
+    #[test]
+    fn test_constraint_count_per_block() {
+        let mut one_block = CostCounter::default();
+        sha256(&mut one_block, &vec![Signal::default(); 55]);
+        let mut two_blocks = CostCounter::default();
+        sha256(&mut two_blocks, &vec![Signal::default(); 56]);
+
+        // One more byte of input only adds its decomposition, the rest is a full compression.
+        let byte_cost = 8 + 1;
+        let block_cost = two_blocks.constraints - one_block.constraints - byte_cost;
+        assert_eq!(one_block.constraints, 55 * byte_cost + block_cost);
+        assert_eq!(block_cost, 27_040);
+    }
+}
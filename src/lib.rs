@@ -0,0 +1,10 @@
+#![allow(clippy::module_inception)]
+
+pub mod circuits;
+pub mod gadgets;
+pub mod r1cs;
+pub mod utils;
+pub mod zk_proofs;
+
+use utils::field::field::FieldElement;
+use utils::polynomial::polynomial::Polynomial;
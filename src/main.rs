@@ -1,27 +1,21 @@
-mod circuits;
-mod r1cs;
-mod utils;
-mod zk_proofs;
-
-use circuits::Circuit;
-use r1cs::r1cs::R1CS;
-use std::{collections::HashMap, env};
-use utils::polynomial::polynomial::Polynomial;
+use std::env;
+use zk_cache::circuits::{parse_inputs, Circuit};
+use zk_cache::r1cs::r1cs::R1CS;
 
 fn main() {
     env::set_var("RUST_BACKTRACE", "1");
     let file_path = "./circuits/sample_circuits/circuit1.json";
 
-    let mut inputs = HashMap::new();
-    inputs.insert("1".to_string(), 1);
-    inputs.insert("x".to_string(), 3);
+    let inputs = parse_inputs(r#"{"x": 3}"#).expect("Failed to parse inputs");
 
     let mut circuit = Circuit::from_file(file_path).expect("Failed to load circuit");
     let variable_map = circuit.hash_and_index_circuit();
 
     let mut r1cs = R1CS::new(variable_map);
     r1cs.generate_r1cs_constraints(&circuit, true);
-    let witness = r1cs.compute_witness(&circuit, inputs);
+    let witness = r1cs
+        .compute_witness(&circuit, inputs)
+        .expect("Failed to compute witness");
 
     println!("{:?}", witness);
 }
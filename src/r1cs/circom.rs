@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use ark_ff::{BigInt, BigInteger, PrimeField};
+
+use crate::circuits::{CircuitError, IndexedMap};
+use crate::r1cs::r1cs::{LinearCombination, R1CS};
+use crate::utils::field::field::FieldElement;
+
+/// Size in bytes of a field element in the iden3 binary formats.
+const FIELD_SIZE: usize = 32;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE_TO_LABEL_SECTION: u32 = 3;
+
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const WITNESS_SECTION: u32 = 2;
+
+impl R1CS {
+    /// Writes the constraint system in the iden3 `.r1cs` binary format used by circom and
+    /// snarkjs.
+    ///
+    /// Wires follow circom's order: the constant `1`, `out` as the public output, the public
+    /// inputs, then the other variables in variable map order. The label of each wire is the
+    /// index of its variable.
+    pub fn write_r1cs<W: Write>(&self, mut writer: W) -> Result<(), CircuitError> {
+        let (order, outputs) = self.wire_order();
+        let mut wires = vec![0; order.len()];
+        for (wire, &index) in order.iter().enumerate() {
+            wires[index] = wire;
+        }
+
+        let mut header = prime();
+        header.extend((order.len() as u32).to_le_bytes());
+        header.extend((outputs as u32).to_le_bytes());
+        header.extend((self.num_public_inputs as u32).to_le_bytes());
+        header.extend(((self.num_private_inputs - outputs) as u32).to_le_bytes());
+        header.extend((order.len() as u64).to_le_bytes());
+        header.extend((self.num_constraints() as u32).to_le_bytes());
+
+        let mut constraints = Vec::new();
+        let (a, b, c) = self.get_constraint_matrices();
+        for ((a, b), c) in a.iter().zip(b).zip(c) {
+            for lc in [a, b, c] {
+                let mut terms: Vec<(usize, FieldElement)> = lc
+                    .iter()
+                    .map(|&(index, coeff)| (wires[index], coeff))
+                    .collect();
+                terms.sort_by_key(|&(wire, _)| wire);
+                constraints.extend((terms.len() as u32).to_le_bytes());
+                for (wire, coeff) in terms {
+                    constraints.extend((wire as u32).to_le_bytes());
+                    constraints.extend(coeff.into_bigint().to_bytes_le());
+                }
+            }
+        }
+
+        let labels: Vec<u8> = order
+            .iter()
+            .flat_map(|&index| (index as u64).to_le_bytes())
+            .collect();
+
+        write_sections(
+            &mut writer,
+            R1CS_MAGIC,
+            1,
+            [
+                (HEADER_SECTION, header),
+                (CONSTRAINTS_SECTION, constraints),
+                (WIRE_TO_LABEL_SECTION, labels),
+            ],
+        )
+    }
+
+    /// Reads a constraint system in the iden3 `.r1cs` binary format, over the field configured
+    /// for this crate.
+    ///
+    /// The file holds no signal names: wire 0 is named `1` and the others `label_{id}` after
+    /// their label. Public outputs are counted among the public inputs, as they precede them.
+    pub fn read_r1cs<R: Read>(reader: R) -> Result<R1CS, CircuitError> {
+        read_r1cs_with_names(reader, |label| Ok(format!("label_{}", label)))
+    }
+
+    /// Reads a constraint system compiled by circom from its `.r1cs` file, naming wires after
+    /// the signals of their labels in the `.sym` file, such as `main.out`. Wire 0 is named `1`.
+    pub fn read_circom<R: Read, S: Read>(r1cs: R, mut sym: S) -> Result<R1CS, CircuitError> {
+        let mut symbols = String::new();
+        sym.read_to_string(&mut symbols)
+            .map_err(|e| CircuitError::FileReadError(e.to_string()))?;
+        let names = read_symbols(&symbols)?;
+
+        read_r1cs_with_names(r1cs, |label| {
+            names
+                .get(&label)
+                .cloned()
+                .ok_or_else(|| deserialization_error(format!("no symbol for label {}", label)))
+        })
+    }
+
+    /// Writes a witness computed by `compute_witness` in the iden3 `.wtns` binary format, its
+    /// values ordered as the wires of `write_r1cs`.
+    pub fn write_wtns<W: Write>(
+        &self,
+        witness: &HashMap<String, FieldElement>,
+        mut writer: W,
+    ) -> Result<(), CircuitError> {
+        let vector = self.witness_vector(witness)?;
+        let values: Vec<FieldElement> = self
+            .wire_order()
+            .0
+            .iter()
+            .map(|&index| vector[index])
+            .collect();
+
+        let mut header = prime();
+        header.extend((values.len() as u32).to_le_bytes());
+        let values: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.into_bigint().to_bytes_le())
+            .collect();
+
+        write_sections(
+            &mut writer,
+            WTNS_MAGIC,
+            2,
+            [(HEADER_SECTION, header), (WITNESS_SECTION, values)],
+        )
+    }
+
+    /// Reads a witness in the iden3 `.wtns` binary format, its values ordered as the wires of
+    /// `write_r1cs`, as a vector ordered as the variable map, which `is_satisfied_by_vector`
+    /// checks. The value of the constant wire must be 1.
+    pub fn read_wtns<R: Read>(&self, mut reader: R) -> Result<Vec<FieldElement>, CircuitError> {
+        let bytes = read_all(&mut reader)?;
+        let sections = read_sections(&bytes, WTNS_MAGIC)?;
+
+        let mut header = Cursor::new(section(&sections, HEADER_SECTION)?);
+        read_prime(&mut header)?;
+        let size = header.u32()? as usize;
+        if size != self.variable_map.len() {
+            return Err(deserialization_error(format!(
+                "expected {} witness values, found {}",
+                self.variable_map.len(),
+                size
+            )));
+        }
+
+        let mut values = Cursor::new(section(&sections, WITNESS_SECTION)?);
+        let mut vector = vec![FieldElement::from(0); size];
+        for index in self.wire_order().0 {
+            vector[index] = values.field_element()?;
+        }
+        if vector[0] != FieldElement::from(1) {
+            return Err(deserialization_error(format!(
+                "expected 1 for the constant wire, found {}",
+                vector[0]
+            )));
+        }
+        Ok(vector)
+    }
+
+    /// Variable index of each wire in circom's order, and the number of public outputs: the
+    /// constant, the public variables, then the others.
+    fn wire_order(&self) -> (Vec<usize>, usize) {
+        let public = self.public_variables();
+        let mut order = vec![0];
+        order.extend(&public);
+        order.extend((1..self.variable_map.len()).filter(|index| !public.contains(index)));
+        (order, public.len() - self.num_public_inputs)
+    }
+}
+
+/// Reads a `.r1cs` file, naming wires other than the constant from their labels.
+fn read_r1cs_with_names<R: Read>(
+    mut reader: R,
+    name: impl Fn(u64) -> Result<String, CircuitError>,
+) -> Result<R1CS, CircuitError> {
+    let bytes = read_all(&mut reader)?;
+    let sections = read_sections(&bytes, R1CS_MAGIC)?;
+
+    let mut header = Cursor::new(section(&sections, HEADER_SECTION)?);
+    read_prime(&mut header)?;
+    let wires = header.u32()? as usize;
+    let public_outputs = header.u32()? as usize;
+    let public_inputs = header.u32()? as usize;
+    let private_inputs = header.u32()? as usize;
+    header.u64()?;
+    let num_constraints = header.u32()? as usize;
+
+    let mut labels = Cursor::new(section(&sections, WIRE_TO_LABEL_SECTION)?);
+    let names = (0..wires)
+        .map(|wire| {
+            let label = labels.u64()?;
+            match wire {
+                0 => Ok("1".to_string()),
+                _ => name(label),
+            }
+        })
+        .collect::<Result<Vec<String>, CircuitError>>()?;
+
+    let variable_map = IndexedMap::from_vector(names);
+    if variable_map.len() != wires {
+        return Err(deserialization_error(
+            "several wires have the same name".to_string(),
+        ));
+    }
+    let mut r1cs = R1CS::new(variable_map);
+    r1cs.num_public_inputs = public_outputs + public_inputs;
+    r1cs.num_private_inputs = private_inputs;
+
+    let mut constraints = Cursor::new(section(&sections, CONSTRAINTS_SECTION)?);
+    for _ in 0..num_constraints {
+        let a = read_linear_combination(&mut constraints, wires)?;
+        let b = read_linear_combination(&mut constraints, wires)?;
+        let c = read_linear_combination(&mut constraints, wires)?;
+        r1cs.enforce(&a, &b, &c);
+    }
+    Ok(r1cs)
+}
+
+/// Maps labels to signal names from the lines `label,wire,component,name` of a `.sym` file.
+fn read_symbols(symbols: &str) -> Result<HashMap<u64, String>, CircuitError> {
+    symbols
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.trim().splitn(4, ',').collect();
+            match fields[..] {
+                [label, _, _, name] => label
+                    .parse()
+                    .map(|label| (label, name.to_string()))
+                    .map_err(|_| deserialization_error(format!("invalid label in {:?}", line))),
+                _ => Err(deserialization_error(format!(
+                    "expected label,wire,component,name, found {:?}",
+                    line
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Field element size and prime, which start the header of iden3 binary files.
+fn prime() -> Vec<u8> {
+    let mut bytes = (FIELD_SIZE as u32).to_le_bytes().to_vec();
+    bytes.extend(FieldElement::MODULUS.to_bytes_le());
+    bytes
+}
+
+fn write_sections<W: Write, const N: usize>(
+    writer: &mut W,
+    magic: &[u8; 4],
+    version: u32,
+    sections: [(u32, Vec<u8>); N],
+) -> Result<(), CircuitError> {
+    let mut bytes = Vec::new();
+    bytes.extend(magic);
+    bytes.extend(version.to_le_bytes());
+    bytes.extend((N as u32).to_le_bytes());
+    for (section, content) in sections {
+        bytes.extend(section.to_le_bytes());
+        bytes.extend((content.len() as u64).to_le_bytes());
+        bytes.extend(content);
+    }
+    writer
+        .write_all(&bytes)
+        .map_err(|e| CircuitError::SerializationError(e.to_string()))
+}
+
+fn read_all<R: Read>(reader: &mut R) -> Result<Vec<u8>, CircuitError> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| CircuitError::FileReadError(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn section<'a>(sections: &HashMap<u32, &'a [u8]>, id: u32) -> Result<&'a [u8], CircuitError> {
+    sections
+        .get(&id)
+        .copied()
+        .ok_or_else(|| deserialization_error(format!("missing section {}", id)))
+}
+
+/// Splits an iden3 binary file into its sections by type, after checking its magic number
+/// and version.
+fn read_sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+) -> Result<HashMap<u32, &'a [u8]>, CircuitError> {
+    let mut cursor = Cursor::new(bytes);
+    if cursor.take(4)? != magic {
+        return Err(deserialization_error(format!(
+            "expected magic number {:?}",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    let version = cursor.u32()?;
+    if version != 1 && version != 2 {
+        return Err(deserialization_error(format!(
+            "unsupported version {}",
+            version
+        )));
+    }
+
+    let mut sections = HashMap::new();
+    for _ in 0..cursor.u32()? {
+        let id = cursor.u32()?;
+        let size = cursor.u64()? as usize;
+        sections.insert(id, cursor.take(size)?);
+    }
+    Ok(sections)
+}
+
+/// Reads the field element size and prime, which must be those of this crate's field.
+fn read_prime(cursor: &mut Cursor) -> Result<(), CircuitError> {
+    let size = cursor.u32()? as usize;
+    let prime = cursor.take(size)?;
+    if size != FIELD_SIZE || prime != FieldElement::MODULUS.to_bytes_le() {
+        return Err(deserialization_error(
+            "field prime does not match the configured field".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn read_linear_combination(
+    cursor: &mut Cursor,
+    wires: usize,
+) -> Result<LinearCombination, CircuitError> {
+    (0..cursor.u32()?)
+        .map(|_| {
+            let wire = cursor.u32()? as usize;
+            if wire >= wires {
+                return Err(deserialization_error(format!("wire {} out of range", wire)));
+            }
+            Ok((wire, cursor.field_element()?))
+        })
+        .collect()
+}
+
+fn deserialization_error(message: String) -> CircuitError {
+    CircuitError::DeserializationError(message)
+}
+
+/// Little-endian reader over the bytes of a section.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Cursor { bytes }
+    }
+
+    fn take(&mut self, size: usize) -> Result<&'a [u8], CircuitError> {
+        if size > self.bytes.len() {
+            return Err(deserialization_error("unexpected end of file".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, CircuitError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, CircuitError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Field element in canonical form, below the prime.
+    fn field_element(&mut self) -> Result<FieldElement, CircuitError> {
+        let mut limbs = [0u64; FIELD_SIZE / 8];
+        for (limb, bytes) in limbs.iter_mut().zip(self.take(FIELD_SIZE)?.chunks(8)) {
+            *limb = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        FieldElement::from_bigint(BigInt(limbs)).ok_or_else(|| {
+            deserialization_error("field element is not reduced modulo the prime".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::Circuit;
+
+    #[test]
+    fn test_r1cs_round_trip() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Multiply",
+                "operands": [{"operation": {"ToBits": {"n": 4}}, "operands": ["x"]},
+                             {"operation": "Add", "operands": ["y", -2]}],
+                "public_inputs": ["y"],
+                "types": {"z": "u8"}}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        assert_eq!(r1cs.num_public_inputs, 1);
+        // x, out and z.
+        assert_eq!(r1cs.num_private_inputs, 3);
+
+        let mut bytes = Vec::new();
+        r1cs.write_r1cs(&mut bytes).unwrap();
+        assert_eq!(&bytes[..4], b"r1cs");
+        // The header follows the section header, n8 and the prime: wires, then public outputs.
+        assert_eq!(bytes[64..68], 1u32.to_le_bytes());
+        let read = R1CS::read_r1cs(bytes.as_slice()).unwrap();
+
+        // Wires are 1, out, y, then the other variables.
+        let out = r1cs.variable_map.get_index(&"out".to_string()).unwrap();
+        let names: Vec<&String> = (0..3)
+            .map(|wire| read.variable_map.get_item(wire).unwrap())
+            .collect();
+        assert_eq!(names, ["1", &format!("label_{}", out), "label_1"]);
+        assert_eq!(read.variable_map.len(), r1cs.variable_map.len());
+        assert_eq!(read.num_constraints(), r1cs.num_constraints());
+        assert_eq!(read.num_public_inputs, 2);
+        assert_eq!(read.num_private_inputs, 2);
+
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(9)),
+            ("y".to_string(), FieldElement::from(7)),
+            ("z".to_string(), FieldElement::from(200)),
+        ]);
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        let mut wtns = Vec::new();
+        r1cs.write_wtns(&witness, &mut wtns).unwrap();
+        let wires = read.read_wtns(wtns.as_slice()).unwrap();
+        assert_eq!(wires[1], witness["out"]);
+        assert!(read.is_satisfied_by_vector(&wires));
+    }
+
+    #[test]
+    fn test_circom_signals_name_wires() {
+        // A circom template `c <== a * b; out <== c + a;` with a public output and input.
+        let variables = ["1", "out", "a", "b", "c"].map(String::from);
+        let mut compiled = R1CS::new(IndexedMap::from_vector(variables.to_vec()));
+        let one = FieldElement::from(1);
+        compiled.enforce(&[(2, one)], &[(3, one)], &[(4, one)]);
+        compiled.enforce(&[(2, one), (4, one)], &[(0, one)], &[(1, one)]);
+        compiled.num_public_inputs = 2;
+        compiled.num_private_inputs = 1;
+        let mut bytes = Vec::new();
+        compiled.write_r1cs(&mut bytes).unwrap();
+        let sym = "1,1,0,main.out\n2,2,0,main.a\n3,3,0,main.b\n4,4,0,main.c\n5,-1,1,main.m.x\n";
+
+        let r1cs = R1CS::read_circom(bytes.as_slice(), sym.as_bytes()).unwrap();
+        let names: Vec<&String> = (0..5)
+            .map(|wire| r1cs.variable_map.get_item(wire).unwrap())
+            .collect();
+        assert_eq!(names, ["1", "main.out", "main.a", "main.b", "main.c"]);
+        assert_eq!(r1cs.num_public_inputs, 2);
+
+        let witness: HashMap<String, FieldElement> = [("1", 1), ("main.a", 3), ("main.b", 4)]
+            .into_iter()
+            .chain([("main.c", 12), ("main.out", 15)])
+            .map(|(name, value)| (name.to_string(), FieldElement::from(value)))
+            .collect();
+        assert!(r1cs.is_satisfied(&witness));
+
+        assert!(matches!(
+            R1CS::read_circom(bytes.as_slice(), &sym.as_bytes()[15..]),
+            Err(CircuitError::DeserializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_reads_circom_compiled_circuit() {
+        // `multiplier.circom` compiled by circom, with `c <== a * b` as its only constraint.
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/r1cs/sample_circuits/");
+        let open = |name: &str| std::fs::File::open(format!("{}{}", dir, name)).unwrap();
+        let r1cs = R1CS::read_circom(open("multiplier.r1cs"), open("multiplier.sym")).unwrap();
+
+        assert_eq!(r1cs.num_constraints(), 1);
+        let names: Vec<&String> = (0..4)
+            .map(|wire| r1cs.variable_map.get_item(wire).unwrap())
+            .collect();
+        assert_eq!(names, ["1", "main.c", "main.a", "main.b"]);
+        assert_eq!(r1cs.num_public_inputs, 1);
+        assert_eq!(r1cs.num_private_inputs, 2);
+
+        let witness = [1, 33, 3, 11].map(FieldElement::from);
+        assert!(r1cs.is_satisfied_by_vector(&witness));
+        assert!(!r1cs.is_satisfied_by_vector(&[1, 34, 3, 11].map(FieldElement::from)));
+    }
+
+    #[test]
+    fn test_wtns_round_trip() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": {"Pow": {"exponent": 5}}, "operands": ["x"], "public_inputs": ["x"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([("x".to_string(), -FieldElement::from(3))]);
+        let witness = r1cs.compute_witness(&circuit, inputs.clone()).unwrap();
+
+        let mut bytes = Vec::new();
+        r1cs.write_wtns(&witness, &mut bytes).unwrap();
+        assert_eq!(&bytes[..8], b"wtns\x02\0\0\0");
+        let read = r1cs.read_wtns(bytes.as_slice()).unwrap();
+        assert_eq!(read, r1cs.compute_witness_vector(&circuit, inputs).unwrap());
+        assert!(r1cs.is_satisfied_by_vector(&read));
+
+        let other = R1CS::new(IndexedMap::from_vector(vec!["1".to_string()]));
+        assert!(matches!(
+            other.read_wtns(bytes.as_slice()),
+            Err(CircuitError::DeserializationError(_))
+        ));
+
+        // The constant wire follows the header section and the witness section header.
+        let constant = 4 + 4 + 4 + (4 + 8 + 4 + FIELD_SIZE + 4) + (4 + 8);
+        let mut other_constant = bytes.clone();
+        other_constant[constant] = 2;
+        match r1cs.read_wtns(other_constant.as_slice()) {
+            Err(CircuitError::DeserializationError(message)) => {
+                assert_eq!(message, "expected 1 for the constant wire, found 2")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        // The last value replaced by the prime itself, which only reduces to 0.
+        let end = bytes.len() - FIELD_SIZE;
+        bytes[end..].copy_from_slice(&FieldElement::MODULUS.to_bytes_le());
+        assert!(matches!(
+            r1cs.read_wtns(bytes.as_slice()),
+            Err(CircuitError::DeserializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_r1cs_reader_rejects_other_files() {
+        let r1cs = R1CS::new(IndexedMap::from_vector(vec!["1".to_string()]));
+        let mut bytes = Vec::new();
+        r1cs.write_r1cs(&mut bytes).unwrap();
+
+        let mut other_prime = bytes.clone();
+        // The prime follows the magic number, version, section count, section header and n8.
+        other_prime[4 + 4 + 4 + 4 + 8 + 4] ^= 1;
+        for bytes in [
+            b"wtns".to_vec(),
+            other_prime,
+            bytes[..bytes.len() - 1].to_vec(),
+        ] {
+            assert!(matches!(
+                R1CS::read_r1cs(bytes.as_slice()),
+                Err(CircuitError::DeserializationError(_))
+            ));
+        }
+    }
+}
@@ -1 +1,3 @@
-pub mod r1cs;
\ No newline at end of file
+pub mod circom;
+pub mod r1cs;
+pub mod tape;
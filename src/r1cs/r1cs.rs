@@ -1,20 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::circuits::{
+    square_and_multiply_chain, Circuit, CircuitError, IndexedMap, Operand, Operation,
+};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::utils::field::field::{bit_length, power_of_two, to_bits_le, FieldElement};
+use crate::utils::hints::hints::{Hint, Hints};
+
+/// A linear combination of witness variables as `(index, coefficient)` pairs.
+pub type LinearCombination = Vec<(usize, FieldElement)>;
 
-use crate::circuits::{Circuit, IndexedMap, Operand, Operation};
 #[allow(unused_variables)]
 #[derive(Debug)]
 struct Constraint {
-    a: Vec<i64>,
-    b: Vec<i64>,
-    c: Vec<i64>,
+    a: LinearCombination,
+    b: LinearCombination,
+    c: LinearCombination,
 }
 
+/// Constraint matrices are stored sparsely, one linear combination per row.
+type Matrix = Vec<LinearCombination>;
+
 #[derive(Debug)]
 pub struct R1CS {
-    a_matrix: Vec<Vec<i64>>,
-    b_matrix: Vec<Vec<i64>>,
-    c_matrix: Vec<Vec<i64>>,
+    a_matrix: Matrix,
+    b_matrix: Matrix,
+    c_matrix: Matrix,
     pub variable_map: IndexedMap<String>,
+    /// Number of public inputs, indexed right after the constant `1`.
+    pub num_public_inputs: usize,
+    /// Number of other inputs of the root circuit, including `out`, indexed after the public
+    /// inputs.
+    pub num_private_inputs: usize,
+    /// Native functions computing the values of `Hint` operations.
+    hints: Hints,
 }
 
 impl R1CS {
@@ -24,16 +43,39 @@ impl R1CS {
             b_matrix: Vec::new(),
             c_matrix: Vec::new(),
             variable_map,
+            num_public_inputs: 0,
+            num_private_inputs: 0,
+            hints: Hints::default(),
         }
     }
 
+    /// Makes `hint` available to `Hint` operations as `name`, besides the built-in hints.
+    pub fn register_hint(&mut self, name: &str, hint: Hint) {
+        self.hints.register(name, hint);
+    }
+
+    pub fn hints(&self) -> &Hints {
+        &self.hints
+    }
+
+    /// Variables whose values are public in proofs, in the order verifiers take them: `out` as
+    /// the public output, unless it is a public input already, then the public inputs.
+    pub fn public_variables(&self) -> Vec<usize> {
+        let public = 1..=self.num_public_inputs;
+        let out = self
+            .variable_map
+            .get_index(&"out".to_string())
+            .filter(|out| !public.contains(out));
+        out.into_iter().chain(public).collect()
+    }
+
     fn add_constraint(&mut self, constraint: Constraint) {
         self.a_matrix.push(constraint.a);
         self.b_matrix.push(constraint.b);
         self.c_matrix.push(constraint.c);
     }
 
-    pub fn get_constraint_matrices(&self) -> (&Vec<Vec<i64>>, &Vec<Vec<i64>>, &Vec<Vec<i64>>) {
+    pub fn get_constraint_matrices(&self) -> (&Matrix, &Matrix, &Matrix) {
         (&self.a_matrix, &self.b_matrix, &self.c_matrix)
     }
 
@@ -41,9 +83,53 @@ impl R1CS {
         &self.variable_map
     }
 
+    pub fn num_constraints(&self) -> usize {
+        self.a_matrix.len()
+    }
+
+    /// Checks that the witness satisfies every constraint `<a, w> * <b, w> = <c, w>`.
+    pub fn is_satisfied(&self, witness: &HashMap<String, FieldElement>) -> bool {
+        let assignment: Vec<FieldElement> = (0..self.variable_map.len())
+            .map(|index| {
+                let name = self.variable_map.get_item(index).unwrap();
+                witness.get(name).copied().unwrap_or_default()
+            })
+            .collect();
+        self.is_satisfied_by_vector(&assignment)
+    }
+
+    /// Checks a witness vector ordered as the variable map against every constraint.
+    pub fn is_satisfied_by_vector(&self, assignment: &[FieldElement]) -> bool {
+        let dot = |row: &LinearCombination| -> FieldElement {
+            row.iter()
+                .map(|&(index, coeff)| coeff * assignment[index])
+                .sum()
+        };
+
+        self.a_matrix
+            .iter()
+            .zip(&self.b_matrix)
+            .zip(&self.c_matrix)
+            .all(|((a, b), c)| dot(a) * dot(b) == dot(c))
+    }
+
+    /// Generates the constraints of the circuit and its nested circuits, in pre-order. The
+    /// traversal keeps an explicit stack, as circuits can be nested deeper than recursion allows.
     pub fn generate_r1cs_constraints(&mut self, circuit: &Circuit, root: bool) {
+        let mut stack = vec![(circuit, root)];
+        while let Some((circuit, root)) = stack.pop() {
+            self.generate_node_constraints(circuit, root);
+            for operand in circuit.operands.iter().rev() {
+                if let Operand::NestedCircuit(nested_circuit) = operand {
+                    stack.push((nested_circuit, false));
+                }
+            }
+        }
+    }
+
+    /// Generates the constraints of the circuit's own operation and type.
+    fn generate_node_constraints(&mut self, circuit: &Circuit, root: bool) {
         let circuit_hash = circuit.get_hash();
-        let length = self.variable_map.len();
 
         let circuit_index = if root {
             match self.variable_map.get_index(&"out".to_string()) {
@@ -56,273 +142,1263 @@ impl R1CS {
                 None => panic!("Cannot find index of current circuit in variable_indices"),
             }
         };
+        let output = vec![(circuit_index, one())];
 
-        let mut constraint = Constraint {
-            b: vec![0; length],
-            c: vec![0; length],
-            a: vec![0; length],
-        };
-
-        constraint.c[circuit_index] = 1;
-
-        let operand1 = &circuit.operands[0];
-        let operand2 = &circuit.operands[1];
+        if !circuit.is_valid() {
+            panic!(
+                "{:?} expects {:?} operands, found {}",
+                circuit.operation,
+                circuit.operation.arity(),
+                circuit.operands.len()
+            );
+        }
+        let terms: Vec<LinearCombination> = circuit
+            .operands
+            .iter()
+            .map(|operand| self.operand_term(operand))
+            .collect();
 
-        match (operand1, operand2) {
-            (Operand::Number(num1), Operand::Number(num2)) => {
-                self.handle_number_number_case(*num1, *num2, &circuit.operation, &mut constraint)
+        match circuit.operation {
+            Operation::Add => {
+                let sum = [terms[0].clone(), terms[1].clone()].concat();
+                self.enforce(&sum, &[(0, one())], &output);
+            }
+            Operation::Multiply => self.enforce(&terms[0], &terms[1], &output),
+            Operation::ToBits { n: bits } | Operation::RangeCheck { bits } => {
+                self.generate_bit_decomposition_constraints(circuit, &output, &terms[0], bits)
+            }
+            Operation::Select => {
+                self.generate_select_constraints(&output, &terms[0], &terms[1], &terms[2])
+            }
+            Operation::Pow { exponent } => {
+                self.generate_pow_constraints(circuit, &output, &terms[0], exponent)
+            }
+            // Hints are checked by the constraints of the operations using them.
+            Operation::Hint { .. } => {}
+            ref operation => {
+                let inputs: Vec<Signal> = terms.into_iter().map(Signal::from_lc).collect();
+                let result = operation
+                    .synthesize(
+                        &mut ConstraintWriter::new(self, Owner::circuit(circuit.hash)),
+                        &inputs,
+                    )
+                    .expect("Operation has no gadget");
+                self.enforce(&result.lc, &[(0, one())], &output);
             }
-            (Operand::Number(num), Operand::Variable(var))
-            | (Operand::Variable(var), Operand::Number(num)) => self.handle_number_variable_case(
-                var.to_string(),
-                *num,
-                &circuit.operation,
-                &mut constraint,
-            ),
-            (Operand::Variable(var1), Operand::Variable(var2)) => self
-                .handle_variable_variable_case(
-                    var1.to_string(),
-                    var2.to_string(),
-                    &circuit.operation,
-                    &mut constraint,
-                ),
-            (Operand::NestedCircuit(nested_circuit), Operand::Number(num))
-            | (Operand::Number(num), Operand::NestedCircuit(nested_circuit)) => self
-                .handle_number_nested_circuit_case(
-                    *num,
-                    nested_circuit,
-                    &circuit.operation,
-                    &mut constraint,
-                ),
-            (Operand::NestedCircuit(nested_circuit), Operand::Variable(var))
-            | (Operand::Variable(var), Operand::NestedCircuit(nested_circuit)) => self
-                .handle_variable_nested_circuit_case(
-                    var.to_string(),
-                    nested_circuit,
-                    &circuit.operation,
-                    &mut constraint,
-                ),
-            (Operand::NestedCircuit(circuit1), Operand::NestedCircuit(circuit2)) => self
-                .handle_double_nested_circuit_case(
-                    circuit1,
-                    circuit2,
-                    &circuit.operation,
-                    &mut constraint,
-                ),
         }
 
-        self.add_constraint(constraint)
+        if let Some(ty) = circuit.ty {
+            let first = circuit.operation.auxiliary_count(circuit.operands.len());
+            let owner = Owner::Circuit {
+                hash: circuit.hash,
+                first,
+            };
+            ty.enforce(
+                &mut ConstraintWriter::new(self, owner),
+                &Signal::from_lc(output.clone()),
+            );
+        }
+        if root {
+            // Inputs precede linearization variables, with typed-only inputs last.
+            let last_input = circuit
+                .types
+                .keys()
+                .map(|input| self.index_of(input))
+                .fold(circuit_index, usize::max);
+            // Public inputs come first, each indexed once however often it is listed.
+            let public_inputs: HashSet<usize> = circuit
+                .public_inputs
+                .iter()
+                .map(|input| self.index_of(input))
+                .filter(|&index| index != 0)
+                .collect();
+            self.num_public_inputs = public_inputs.len();
+            self.num_private_inputs = last_input - self.num_public_inputs;
+            for (input, ty) in &circuit.types {
+                let term = vec![(self.index_of(input), one())];
+                ty.enforce(
+                    &mut ConstraintWriter::new(self, Owner::Input(input)),
+                    &Signal::from_lc(term),
+                );
+            }
+        }
     }
 
-    fn handle_number_number_case(
-        &mut self,
-        num1: i64,
-        num2: i64,
-        operation: &Operation,
-        constraint: &mut Constraint,
-    ) {
-        match operation {
-            Operation::Add => {
-                constraint.a[0] = num1 + num2;
-                constraint.b[0] = 1;
-            }
-            Operation::Multiply => {
-                constraint.a[0] = num1;
-                constraint.b[0] = num2;
+    /// Resolves an operand to the linear combination of variables holding its value.
+    fn operand_term(&self, operand: &Operand) -> LinearCombination {
+        match operand {
+            Operand::Number(num) => vec![(0, FieldElement::from(*num))],
+            Operand::Variable(var) => vec![(self.index_of(var), one())],
+            Operand::Fixed(fixed) => vec![(0, fixed.encode())],
+            Operand::NestedCircuit(nested_circuit) => {
+                vec![(self.index_of(&nested_circuit.hash.to_string()), one())]
             }
         }
     }
 
-    fn handle_number_variable_case(
+    /// Adds the constraint `a * b = c`, accumulating repeated indices.
+    pub(crate) fn enforce(
         &mut self,
-        var: String,
-        num: i64,
-        operation: &Operation,
-        constraint: &mut Constraint,
+        a: &[(usize, FieldElement)],
+        b: &[(usize, FieldElement)],
+        c: &[(usize, FieldElement)],
     ) {
-        let index = match self.variable_map.get_index(&var) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        match operation {
-            Operation::Add => {
-                constraint.a[0] = num;
-                constraint.a[index] = 1;
-                constraint.b[0] = 1;
-            }
-            Operation::Multiply => {
-                constraint.a[0] = num;
-                constraint.b[index] = 1;
-            }
-        }
+        self.add_constraint(Constraint {
+            a: normalize(a.to_vec()),
+            b: normalize(b.to_vec()),
+            c: normalize(c.to_vec()),
+        });
     }
 
-    fn handle_variable_variable_case(
+    fn generate_bit_decomposition_constraints(
         &mut self,
-        var1: String,
-        var2: String,
-        operation: &Operation,
-        constraint: &mut Constraint,
+        circuit: &Circuit,
+        output: &[(usize, FieldElement)],
+        operand: &[(usize, FieldElement)],
+        bits: usize,
     ) {
-        let index1 = match self.variable_map.get_index(&var1) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        let index2 = match self.variable_map.get_index(&var2) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        match operation {
-            Operation::Add => {
-                if var1 == var2 {
-                    constraint.a[index1] = 2;
-                    constraint.b[0] = 1;
-                } else {
-                    constraint.a[index1] = 1;
-                    constraint.a[index2] = 1;
-                    constraint.b[0] = 1;
-                }
-            }
-            Operation::Multiply => {
-                constraint.a[index1] = 1;
-                constraint.b[index2] = 1;
-            }
+        let bit_indices: Vec<usize> = (0..bits)
+            .map(|i| self.index_of(&Circuit::auxiliary_variable(circuit.hash, i)))
+            .collect();
+
+        // The output is the recomposition of the bits, sum(2^i * b_i) * 1 = out...
+        let recomposition: LinearCombination = bit_indices
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| (index, power_of_two(i)))
+            .collect();
+        self.enforce(&recomposition, &[(0, one())], output);
+
+        // ...and equals the operand.
+        self.enforce(operand, &[(0, one())], output);
+
+        // Booleanity of every bit: b_i * (b_i - 1) = 0.
+        for &index in &bit_indices {
+            self.enforce(&[(index, one())], &[(index, one()), (0, -one())], &[]);
         }
     }
 
-    fn handle_number_nested_circuit_case(
+    fn generate_select_constraints(
         &mut self,
-        num: i64,
-        circuit: &Circuit,
-        operation: &Operation,
-        constraint: &mut Constraint,
+        output: &[(usize, FieldElement)],
+        condition: &[(usize, FieldElement)],
+        when_true: &[(usize, FieldElement)],
+        when_false: &[(usize, FieldElement)],
     ) {
-        let index = match self.variable_map.get_index(&circuit.hash.to_string()) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        match operation {
-            Operation::Add => {
-                constraint.a[0] = num;
-                constraint.a[index] = 1;
-                constraint.b[0] = 1;
-            }
-            Operation::Multiply => {
-                constraint.a[0] = num;
-                constraint.b[index] = 1;
-            }
-        }
-        self.generate_r1cs_constraints(&circuit, false);
+        // out = b + cond * (a - b), i.e. cond * (a - b) = out - b.
+        let difference = [when_true.to_vec(), negate(when_false)].concat();
+        let shifted_output = [output.to_vec(), negate(when_false)].concat();
+        self.enforce(condition, &difference, &shifted_output);
+
+        // cond * (cond - 1) = 0
+        let condition_minus_one = [condition.to_vec(), vec![(0, -one())]].concat();
+        self.enforce(condition, &condition_minus_one, &[]);
     }
 
-    fn handle_variable_nested_circuit_case(
+    fn generate_pow_constraints(
         &mut self,
-        var: String,
         circuit: &Circuit,
-        operation: &Operation,
-        constraint: &mut Constraint,
+        output: &[(usize, FieldElement)],
+        base: &[(usize, FieldElement)],
+        exponent: u64,
     ) {
-        let nested_index = match self.variable_map.get_index(&circuit.hash.to_string()) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
+        match exponent {
+            0 => return self.enforce(&[(0, one())], &[(0, one())], output),
+            1 => return self.enforce(base, &[(0, one())], output),
+            _ => {}
+        }
 
-        let var_index = match self.variable_map.get_index(&var) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        match operation {
-            Operation::Add => {
-                constraint.a[nested_index] = 1;
-                constraint.a[var_index] = 1;
-                constraint.b[0] = 1;
-            }
-            Operation::Multiply => {
-                constraint.a[nested_index] = 1;
-                constraint.b[var_index] = 1;
-            }
+        let chain = square_and_multiply_chain(exponent);
+        let mut accumulator = base.to_vec();
+        for (step, &multiply) in chain.iter().enumerate() {
+            let target = if step + 1 == chain.len() {
+                output.to_vec()
+            } else {
+                vec![(
+                    self.index_of(&Circuit::auxiliary_variable(circuit.hash, step)),
+                    one(),
+                )]
+            };
+
+            let factor = if multiply { base } else { &accumulator };
+            self.enforce(&accumulator, factor, &target);
+            accumulator = target;
         }
-        self.generate_r1cs_constraints(&circuit, false);
     }
 
-    fn handle_double_nested_circuit_case(
-        &mut self,
-        circuit1: &Circuit,
-        circuit2: &Circuit,
-        operation: &Operation,
-        constraint: &mut Constraint,
-    ) {
-        let index1 = match self.variable_map.get_index(&circuit1.hash.to_string()) {
-            Some(index) => index,
-            None => panic!("Cannot find index of variable in variable_indices"),
-        };
-        let index2 = match self.variable_map.get_index(&circuit2.hash.to_string()) {
+    fn index_of(&self, var: &String) -> usize {
+        match self.variable_map.get_index(var) {
             Some(index) => index,
             None => panic!("Cannot find index of variable in variable_indices"),
-        };
-
-        match operation {
-            Operation::Add => {
-                constraint.b[0] = 1;
-                if index1 == index2 {
-                    constraint.a[index1] = 2;
-                } else {
-                    constraint.a[index1] = 1;
-                    constraint.a[index2] = 1;
-                }
-            }
-            Operation::Multiply => {
-                constraint.a[index1] = 1;
-                constraint.b[index2] = 1;
-            }
         }
-
-        self.generate_r1cs_constraints(&circuit1, false);
-        self.generate_r1cs_constraints(&circuit2, false);
     }
 
+    /// Computes the witness as a map from variable names to values, filling in the constant
+    /// `1`. Fails with an `EvaluationError` when the inputs name the constant, miss a variable
+    /// or hold a value out of range for its type, or when an operation is undefined on its
+    /// operands.
     pub fn compute_witness(
         &self,
         circuit: &Circuit,
-        inputs: HashMap<String, i64>,
-    ) -> HashMap<String, i64> {
-        let mut witness: HashMap<String, i64> = HashMap::new();
+        inputs: HashMap<String, FieldElement>,
+    ) -> Result<HashMap<String, FieldElement>, CircuitError> {
+        if inputs.contains_key("1") {
+            return Err(evaluation_error(
+                "Input 1 is reserved for the constant".to_string(),
+            ));
+        }
+        let mut witness = inputs;
+        witness.insert("1".to_string(), one());
 
-        for (var, value) in inputs {
-            witness.insert(var, value);
+        for (input, ty) in &circuit.types {
+            let value = input_value(&witness, input)?;
+            if !ty.contains(&value) {
+                return Err(evaluation_error(format!(
+                    "Input {} = {} is out of range for {:?}",
+                    input, value, ty
+                )));
+            }
+            ty.enforce(
+                &mut WitnessWriter::new(&mut witness, Owner::Input(input)),
+                &Signal::from_value(value),
+            );
         }
 
-        let output = self.evaluate_circuit_recursively(circuit, &mut witness, true);
+        let output = self.evaluate_circuit(circuit, &mut witness)?;
         witness.insert("out".to_string(), output);
-        witness
+        Ok(witness)
     }
 
-    fn evaluate_circuit_recursively(
+    /// Computes the witness as a vector `w` where `w[i]` is the value of the variable indexed
+    /// `i` in the variable map, failing if some variable gets no value.
+    pub fn compute_witness_vector(
         &self,
         circuit: &Circuit,
-        witness: &mut HashMap<String, i64>,
-        root: bool,
-    ) -> i64 {
-        let mut values: [i64; 2] = [0; 2];
-        for (i, operand) in circuit.operands.iter().enumerate() {
-            values[i] = match operand {
-                Operand::Number(num) => *num,
-                Operand::Variable(var) => *witness.get(var).expect("Missing input variable"),
-                Operand::NestedCircuit(nested_circuit) => {
-                    self.evaluate_circuit_recursively(nested_circuit, witness, false)
+        inputs: HashMap<String, FieldElement>,
+    ) -> Result<Vec<FieldElement>, CircuitError> {
+        self.witness_vector(&self.compute_witness(circuit, inputs)?)
+    }
+
+    /// Orders the values of a witness computed by `compute_witness` as the variable map,
+    /// failing if some variable has no value.
+    pub fn witness_vector(
+        &self,
+        witness: &HashMap<String, FieldElement>,
+    ) -> Result<Vec<FieldElement>, CircuitError> {
+        (0..self.variable_map.len())
+            .map(|index| {
+                let name = self.variable_map.get_item(index).unwrap();
+                witness.get(name).copied().ok_or_else(|| {
+                    CircuitError::EvaluationError(format!(
+                        "Missing value for witness slot {} ({})",
+                        index, name
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Evaluates the circuit bottom-up, recording intermediate values in the witness. The
+    /// traversal keeps an explicit stack, as circuits can be nested deeper than recursion allows.
+    fn evaluate_circuit(
+        &self,
+        circuit: &Circuit,
+        witness: &mut HashMap<String, FieldElement>,
+    ) -> Result<FieldElement, CircuitError> {
+        // Circuits being evaluated, with the values of their operands so far.
+        let mut stack = vec![(circuit, Vec::with_capacity(circuit.operands.len()))];
+        loop {
+            let (circuit, values) = stack.last_mut().unwrap();
+            match circuit.operands.get(values.len()) {
+                Some(Operand::NestedCircuit(nested_circuit)) => stack.push((
+                    nested_circuit,
+                    Vec::with_capacity(nested_circuit.operands.len()),
+                )),
+                Some(operand) => values.push(match operand {
+                    Operand::Number(num) => FieldElement::from(*num),
+                    Operand::Variable(var) => input_value(witness, var)?,
+                    Operand::Fixed(fixed) => fixed.encode(),
+                    Operand::NestedCircuit(_) => unreachable!(),
+                }),
+                None => {
+                    let (circuit, values) = stack.pop().unwrap();
+                    let output = self.evaluate_node(circuit, values, witness, stack.is_empty())?;
+                    match stack.last_mut() {
+                        Some((_, values)) => values.push(output),
+                        None => return Ok(output),
+                    }
                 }
             }
         }
+    }
 
+    /// Computes the output of a circuit from the values of its operands, recording the
+    /// auxiliary variables it allocates in the witness.
+    fn evaluate_node(
+        &self,
+        circuit: &Circuit,
+        values: Vec<FieldElement>,
+        witness: &mut HashMap<String, FieldElement>,
+        root: bool,
+    ) -> Result<FieldElement, CircuitError> {
         let output = match circuit.operation {
             Operation::Add => values[0] + values[1],
             Operation::Multiply => values[0] * values[1],
+            Operation::Select => {
+                if values[0] != FieldElement::from(0) && values[0] != one() {
+                    return Err(evaluation_error(format!(
+                        "Select condition must be boolean, found {}",
+                        values[0]
+                    )));
+                }
+                values[2] + values[0] * (values[1] - values[2])
+            }
+            Operation::Pow { exponent } => {
+                let chain = square_and_multiply_chain(exponent);
+                let mut accumulator = if exponent == 0 { one() } else { values[0] };
+                for (step, &multiply) in chain.iter().enumerate() {
+                    accumulator *= if multiply { values[0] } else { accumulator };
+                    if step + 1 < chain.len() {
+                        let name = Circuit::auxiliary_variable(circuit.hash, step);
+                        witness.insert(name, accumulator);
+                    }
+                }
+                accumulator
+            }
+            Operation::ToBits { n: bits } | Operation::RangeCheck { bits } => {
+                let value = values[0];
+                if bit_length(&value) > bits {
+                    return Err(evaluation_error(format!(
+                        "Value {} does not fit in {} bits",
+                        value, bits
+                    )));
+                }
+                for (i, bit) in to_bits_le(&value, bits).into_iter().enumerate() {
+                    witness.insert(
+                        Circuit::auxiliary_variable(circuit.hash, i),
+                        FieldElement::from(bit),
+                    );
+                }
+                value
+            }
+            Operation::Hint { ref name, output } => {
+                let advice = self.hints.compute(name, &values)?;
+                *advice.get(output).ok_or_else(|| {
+                    evaluation_error(format!(
+                        "Hint {} computed {} values, found output {}",
+                        name,
+                        advice.len(),
+                        output
+                    ))
+                })?
+            }
+            ref operation => {
+                let inputs: Vec<Signal> = values.into_iter().map(Signal::from_value).collect();
+                let mut writer = WitnessWriter::new(witness, Owner::circuit(circuit.hash));
+                let result = operation.synthesize(&mut writer, &inputs);
+                if let Some(error) = writer.error {
+                    return Err(evaluation_error(error));
+                }
+                result
+                    .and_then(|signal| signal.value)
+                    .expect("Operation has no gadget")
+            }
         };
 
+        if let Some(ty) = circuit.ty {
+            if !ty.contains(&output) {
+                return Err(evaluation_error(format!(
+                    "Value {} is out of range for {:?}",
+                    output, ty
+                )));
+            }
+            let first = circuit.operation.auxiliary_count(circuit.operands.len());
+            let owner = Owner::Circuit {
+                hash: circuit.hash,
+                first,
+            };
+            ty.enforce(
+                &mut WitnessWriter::new(witness, owner),
+                &Signal::from_value(output),
+            );
+        }
         if !root {
             witness.insert(circuit.hash.to_string(), output);
         }
 
-        output
+        Ok(output)
+    }
+}
+
+fn evaluation_error(message: String) -> CircuitError {
+    CircuitError::EvaluationError(message)
+}
+
+fn input_value(
+    witness: &HashMap<String, FieldElement>,
+    name: &str,
+) -> Result<FieldElement, CircuitError> {
+    witness
+        .get(name)
+        .copied()
+        .ok_or_else(|| evaluation_error(format!("Missing input variable {}", name)))
+}
+
+/// Owner of the auxiliary variables a gadget allocates, which determines their names.
+#[derive(Clone, Copy)]
+enum Owner<'a> {
+    /// The circuit with the given hash, whose auxiliary variables are numbered from `first`.
+    Circuit { hash: u64, first: usize },
+    /// The input whose declared type is being enforced.
+    Input(&'a str),
+}
+
+impl Owner<'_> {
+    fn circuit(hash: u64) -> Self {
+        Owner::Circuit { hash, first: 0 }
+    }
+
+    fn auxiliary_variable(&self, index: usize) -> String {
+        match *self {
+            Owner::Circuit { hash, first } => Circuit::auxiliary_variable(hash, first + index),
+            Owner::Input(input) => Circuit::type_variable(input, index),
+        }
+    }
+}
+
+/// Emits a gadget's constraints into the R1CS, binding its auxiliary variables to the ones
+/// allocated for their owner.
+struct ConstraintWriter<'a> {
+    r1cs: &'a mut R1CS,
+    owner: Owner<'a>,
+    next_auxiliary: usize,
+}
+
+impl<'a> ConstraintWriter<'a> {
+    fn new(r1cs: &'a mut R1CS, owner: Owner<'a>) -> Self {
+        ConstraintWriter {
+            r1cs,
+            owner,
+            next_auxiliary: 0,
+        }
+    }
+}
+
+impl ConstraintSystem for ConstraintWriter<'_> {
+    fn allocate(&mut self, _value: Option<FieldElement>) -> Signal {
+        let name = self.owner.auxiliary_variable(self.next_auxiliary);
+        self.next_auxiliary += 1;
+        Signal::from_lc(vec![(self.r1cs.index_of(&name), one())])
+    }
+
+    fn enforce(&mut self, a: &Signal, b: &Signal, c: &Signal) {
+        self.r1cs.enforce(&a.lc, &b.lc, &c.lc);
+    }
+}
+
+/// Records the values of a gadget's auxiliary variables in the witness, and the first
+/// constraint they fail to satisfy.
+struct WitnessWriter<'a> {
+    witness: &'a mut HashMap<String, FieldElement>,
+    owner: Owner<'a>,
+    next_auxiliary: usize,
+    error: Option<String>,
+}
+
+impl<'a> WitnessWriter<'a> {
+    fn new(witness: &'a mut HashMap<String, FieldElement>, owner: Owner<'a>) -> Self {
+        WitnessWriter {
+            witness,
+            owner,
+            next_auxiliary: 0,
+            error: None,
+        }
+    }
+}
+
+impl ConstraintSystem for WitnessWriter<'_> {
+    fn allocate(&mut self, value: Option<FieldElement>) -> Signal {
+        let value = value.expect("Missing value for auxiliary variable");
+        let name = self.owner.auxiliary_variable(self.next_auxiliary);
+        self.next_auxiliary += 1;
+        self.witness.insert(name, value);
+        Signal::from_value(value)
+    }
+
+    fn enforce(&mut self, a: &Signal, b: &Signal, c: &Signal) {
+        if let (Some(a), Some(b), Some(c)) = (a.value, b.value, c.value) {
+            if a * b != c && self.error.is_none() {
+                self.error = Some(format!("Unsatisfiable constraint: {} * {} != {}", a, b, c));
+            }
+        }
+    }
+}
+
+/// Sums the coefficients of repeated indices and drops the ones that cancel out.
+pub fn normalize(mut lc: LinearCombination) -> LinearCombination {
+    lc.sort_by_key(|&(index, _)| index);
+
+    let mut normalized: LinearCombination = Vec::with_capacity(lc.len());
+    for (index, coeff) in lc {
+        match normalized.last_mut() {
+            Some((last, sum)) if *last == index => *sum += coeff,
+            _ => normalized.push((index, coeff)),
+        }
+    }
+    normalized.retain(|&(_, coeff)| coeff != FieldElement::from(0));
+    normalized
+}
+
+fn one() -> FieldElement {
+    FieldElement::from(1)
+}
+
+fn negate(terms: &[(usize, FieldElement)]) -> LinearCombination {
+    terms
+        .iter()
+        .map(|&(index, coeff)| (index, -coeff))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::parse_inputs;
+    use crate::utils::babyjubjub::babyjubjub::{to_field, Point};
+    use crate::utils::eddsa::eddsa::{
+        circomlibjs_signature, small_order_forgery, PrivateKey, Signature,
+    };
+    use crate::utils::fixed::fixed::decode;
+    use crate::utils::merkle::merkle::{poseidon_hasher, MerkleHasher, SparseMerkleTree};
+    use crate::utils::mimc::mimc::{mimc7, mimc_feistel};
+    use crate::utils::poseidon::poseidon::poseidon_hash;
+    use ark_ff::PrimeField;
+    use sha2::{Digest, Sha256};
+
+    fn build(json: &str) -> (Circuit, R1CS) {
+        let mut circuit: Circuit = serde_json::from_str(json).unwrap();
+        let variable_map = circuit.hash_and_index_circuit();
+        let mut r1cs = R1CS::new(variable_map);
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        (circuit, r1cs)
+    }
+
+    fn evaluation_error(result: Result<HashMap<String, FieldElement>, CircuitError>) -> String {
+        match result {
+            Err(CircuitError::EvaluationError(message)) => message,
+            result => panic!("expected an evaluation error, found {:?}", result),
+        }
+    }
+
+    fn assignment(values: &[(&str, i64)]) -> HashMap<String, FieldElement> {
+        let mut assignment = HashMap::new();
+        for &(name, value) in values {
+            assignment.insert(name.to_string(), FieldElement::from(value));
+        }
+        assignment
+    }
+
+    #[test]
+    fn test_sample_circuit_witness_satisfies_constraints() {
+        let file_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/circuits/sample_circuits/circuit1.json"
+        );
+        let mut circuit = Circuit::from_file(file_path).unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 3)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from((9 + 5) * (6 * 5)));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_bit_decomposition_witness_satisfies_constraints() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": {"ToBits": {"n": 4}},
+                "operands": [{"operation": "Add", "operands": ["x", 2]}]}"#,
+        );
+        assert_eq!(r1cs.num_constraints(), 4 + 2 + 1);
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 9)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(11));
+        let bits: Vec<FieldElement> = (0..4)
+            .map(|i| witness[&Circuit::auxiliary_variable(circuit.hash, i)])
+            .collect();
+        assert_eq!(bits, [1, 1, 0, 1].map(FieldElement::from));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_range_check_rejects_tampered_bits() {
+        let (circuit, r1cs) =
+            build(r#"{"operation": {"RangeCheck": {"bits": 3}}, "operands": ["x"]}"#);
+
+        let mut witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 5)]))
+            .unwrap();
+        assert!(r1cs.is_satisfied(&witness));
+
+        witness.insert(
+            Circuit::auxiliary_variable(circuit.hash, 1),
+            FieldElement::from(2),
+        );
+        witness.insert(
+            Circuit::auxiliary_variable(circuit.hash, 2),
+            FieldElement::from(0),
+        );
+        assert!(!r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_select_picks_operand_by_condition() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Select",
+                "operands": ["c", {"operation": "Multiply", "operands": ["x", "x"]}, 7]}"#,
+        );
+        assert_eq!(r1cs.num_constraints(), 2 + 1);
+
+        for (condition, expected) in [(1, 16), (0, 7)] {
+            let inputs = assignment(&[("c", condition), ("x", 4)]);
+            let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+            assert_eq!(witness["out"], FieldElement::from(expected));
+            assert!(r1cs.is_satisfied(&witness));
+        }
+    }
+
+    #[test]
+    fn test_pow_uses_logarithmic_constraints() {
+        for exponent in [0u64, 1, 2, 13, 32] {
+            let json = format!(
+                r#"{{"operation": {{"Pow": {{"exponent": {}}}}}, "operands": ["x"]}}"#,
+                exponent
+            );
+            let (circuit, r1cs) = build(&json);
+            let chain_length = square_and_multiply_chain(exponent).len();
+            assert_eq!(r1cs.num_constraints(), chain_length.max(1));
+
+            let witness = r1cs
+                .compute_witness(&circuit, assignment(&[("x", 3)]))
+                .unwrap();
+            assert_eq!(
+                witness["out"],
+                FieldElement::from(3u128.pow(exponent as u32))
+            );
+            assert!(r1cs.is_satisfied(&witness));
+        }
+    }
+
+    #[test]
+    fn test_select_rejects_non_boolean_condition() {
+        let (_, r1cs) = build(r#"{"operation": "Select", "operands": ["c", "x", "y"]}"#);
+
+        // c = 2, x = 5, y = 3 satisfies the selection constraint for out = 7 but not booleanity.
+        let witness = assignment(&[("c", 2), ("x", 5), ("y", 3), ("out", 7)]);
+        assert!(!r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_range_check_rejects_out_of_range_input() {
+        let (circuit, r1cs) =
+            build(r#"{"operation": {"RangeCheck": {"bits": 3}}, "operands": ["x"]}"#);
+
+        let witness = r1cs.compute_witness(&circuit, assignment(&[("x", 8)]));
+        assert_eq!(evaluation_error(witness), "Value 8 does not fit in 3 bits");
+    }
+
+    #[test]
+    fn test_poseidon_operation_matches_native_hash() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Poseidon",
+                "operands": ["x", {"operation": "Add", "operands": ["x", 1]}, 7]}"#,
+        );
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 3)]))
+            .unwrap();
+        let expected = poseidon_hash(&[3, 4, 7].map(FieldElement::from));
+        assert_eq!(witness["out"], expected);
+        assert!(r1cs.is_satisfied(&witness));
+
+        let mut tampered = witness.clone();
+        tampered.insert("out".to_string(), expected + one());
+        assert!(!r1cs.is_satisfied(&tampered));
+    }
+
+    #[test]
+    fn test_mimc_operations_match_native_implementations() {
+        let (circuit, r1cs) =
+            build(r#"{"operation": {"MiMC": {"rounds": 91}}, "operands": ["x", "k"]}"#);
+        assert_eq!(r1cs.num_constraints(), 4 * 91 + 1);
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 7), ("k", 13)]))
+            .unwrap();
+        let expected = mimc7(FieldElement::from(7), FieldElement::from(13), 91);
+        assert_eq!(witness["out"], expected);
+        assert!(r1cs.is_satisfied(&witness));
+
+        let (circuit, r1cs) =
+            build(r#"{"operation": {"MiMCFeistel": {"rounds": 220}}, "operands": ["l", "r", 0]}"#);
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("l", 1), ("r", 2)]))
+            .unwrap();
+        let (left, _) = mimc_feistel(
+            FieldElement::from(1),
+            FieldElement::from(2),
+            one() - one(),
+            220,
+        );
+        assert_eq!(witness["out"], left);
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_sha256_operation_matches_native_digest() {
+        let digest = Sha256::digest(b"abc");
+        for (output, half) in digest.chunks(16).enumerate() {
+            let (circuit, r1cs) = build(&format!(
+                r#"{{"operation": {{"Sha256": {{"output": {}}}}}, "operands": ["a", "b", 99]}}"#,
+                output
+            ));
+            let cost = Operation::Sha256 { output }.gadget_cost(3).unwrap();
+            assert_eq!(r1cs.num_constraints(), cost.constraints + 1);
+
+            let witness = r1cs
+                .compute_witness(&circuit, assignment(&[("a", 97), ("b", 98)]))
+                .unwrap();
+            assert_eq!(witness["out"], FieldElement::from_be_bytes_mod_order(half));
+            assert!(r1cs.is_satisfied(&witness));
+        }
+
+        let circuit =
+            Circuit::from_json(r#"{"operation": {"Sha256": {"output": 2}}, "operands": ["a"]}"#)
+                .unwrap();
+        assert!(!circuit.is_valid());
+    }
+
+    fn merkle_membership_witness<H: MerkleHasher>(
+        hash: &str,
+        hasher: H,
+        tamper: bool,
+    ) -> (R1CS, Result<HashMap<String, FieldElement>, CircuitError>) {
+        let leaves: Vec<FieldElement> = (10..16).map(FieldElement::from).collect();
+        let tree = SparseMerkleTree::from_leaves(3, hasher, &leaves);
+        let proof = tree.proof(5);
+
+        let json = format!(
+            r#"{{"operation": {{"MerkleMembership": {{"depth": 3, "hash": {}}}}},
+                "operands": ["leaf", "root", "p0", "p1", "p2", "d0", "d1", "d2"],
+                "public_inputs": ["root"]}}"#,
+            hash
+        );
+        let (circuit, r1cs) = build(&json);
+        assert_eq!(r1cs.variable_map.get_index(&"root".to_string()), Some(1));
+
+        let mut inputs = assignment(&[]);
+        let leaf = if tamper {
+            proof.leaf + one()
+        } else {
+            proof.leaf
+        };
+        inputs.insert("leaf".to_string(), leaf);
+        inputs.insert("root".to_string(), tree.root());
+        for level in 0..3 {
+            inputs.insert(format!("p{}", level), proof.path[level]);
+            inputs.insert(format!("d{}", level), proof.directions[level].into());
+        }
+        let witness = r1cs.compute_witness(&circuit, inputs);
+        if let Ok(witness) = &witness {
+            assert_eq!(witness["out"], tree.root());
+        }
+        (r1cs, witness)
+    }
+
+    #[test]
+    fn test_merkle_membership_with_pluggable_hash() {
+        let (r1cs, witness) = merkle_membership_witness(r#""Poseidon""#, poseidon_hasher, false);
+        assert!(r1cs.is_satisfied(&witness.unwrap()));
+
+        let mimc_hasher = |left, right| mimc7(left, right, 91);
+        let hash = r#"{"MiMC": {"rounds": 91}}"#;
+        let (r1cs, witness) = merkle_membership_witness(hash, mimc_hasher, false);
+        assert!(r1cs.is_satisfied(&witness.unwrap()));
+    }
+
+    #[test]
+    fn test_merkle_membership_rejects_wrong_leaf() {
+        let (_, witness) = merkle_membership_witness(r#""Poseidon""#, poseidon_hasher, true);
+        assert!(evaluation_error(witness).starts_with("Unsatisfiable constraint"));
+    }
+
+    #[test]
+    fn test_merkle_membership_rejects_unsupported_hash() {
+        for hash in ["Multiply", "EdDSAVerify"] {
+            let json = format!(
+                r#"{{"operation": {{"MerkleMembership": {{"depth": 1, "hash": "{}"}}}},
+                    "operands": ["leaf", "root", "p0", "d0"]}}"#,
+                hash
+            );
+            match Circuit::from_json(&json) {
+                Err(CircuitError::DeserializationError(message)) => {
+                    assert!(message.contains("cannot hash Merkle tree nodes"))
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
+        }
+    }
+
+    fn signed_by_one_of_witness(
+        forge: bool,
+    ) -> (R1CS, Result<HashMap<String, FieldElement>, CircuitError>) {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<PrivateKey> = (0..4).map(|_| PrivateKey::random(&mut rng)).collect();
+        let leaves: Vec<FieldElement> = keys
+            .iter()
+            .map(|key| {
+                let public_key = key.public_key();
+                poseidon_hash(&[public_key.x, public_key.y])
+            })
+            .collect();
+        let tree = SparseMerkleTree::from_leaves(2, poseidon_hasher, &leaves);
+        let proof = tree.proof(2);
+
+        // The signature is valid and the key belongs to the set whose root is public.
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Multiply",
+                "operands": [
+                    {"operation": "EdDSAVerify", "operands": ["ax", "ay", "rx", "ry", "s", "m"]},
+                    {"operation": {"MerkleMembership": {"depth": 2, "hash": "Poseidon"}},
+                     "operands": [{"operation": "Poseidon", "operands": ["ax", "ay"]},
+                                  "root", "p0", "p1", "d0", "d1"]}],
+                "public_inputs": ["root", "m"]}"#,
+        );
+
+        let message = FieldElement::from(42);
+        let signer = if forge { &keys[3] } else { &keys[2] };
+        let signature = signer.sign(message);
+        let public_key = keys[2].public_key();
+
+        let mut inputs = assignment(&[]);
+        for (name, value) in [
+            ("ax", public_key.x),
+            ("ay", public_key.y),
+            ("rx", signature.r8.x),
+            ("ry", signature.r8.y),
+            ("s", to_field(&signature.s)),
+            ("m", message),
+            ("root", tree.root()),
+        ] {
+            inputs.insert(name.to_string(), value);
+        }
+        for level in 0..2 {
+            inputs.insert(format!("p{}", level), proof.path[level]);
+            inputs.insert(format!("d{}", level), proof.directions[level].into());
+        }
+        let witness = r1cs.compute_witness(&circuit, inputs);
+        if let Ok(witness) = &witness {
+            assert_eq!(witness["out"], tree.root());
+        }
+        (r1cs, witness)
+    }
+
+    #[test]
+    fn test_eddsa_signature_from_one_of_a_set_of_keys() {
+        let (r1cs, witness) = signed_by_one_of_witness(false);
+        assert!(r1cs.is_satisfied(&witness.unwrap()));
+    }
+
+    #[test]
+    fn test_eddsa_rejects_signature_from_another_key() {
+        let (_, witness) = signed_by_one_of_witness(true);
+        assert!(evaluation_error(witness).starts_with("Unsatisfiable constraint"));
+    }
+
+    fn eddsa_witness(
+        (public_key, message, signature): (Point, FieldElement, Signature),
+    ) -> Result<HashMap<String, FieldElement>, CircuitError> {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "EdDSAVerify", "operands": ["ax", "ay", "rx", "ry", "s", "m"]}"#,
+        );
+        let mut inputs = assignment(&[]);
+        for (name, value) in [
+            ("ax", public_key.x),
+            ("ay", public_key.y),
+            ("rx", signature.r8.x),
+            ("ry", signature.r8.y),
+            ("s", to_field(&signature.s)),
+            ("m", message),
+        ] {
+            inputs.insert(name.to_string(), value);
+        }
+        let witness = r1cs.compute_witness(&circuit, inputs)?;
+        assert!(r1cs.is_satisfied(&witness));
+        Ok(witness)
+    }
+
+    #[test]
+    fn test_eddsa_accepts_circomlibjs_signature() {
+        let witness = eddsa_witness(circomlibjs_signature()).unwrap();
+        assert_eq!(witness["out"], one());
+    }
+
+    #[test]
+    fn test_eddsa_rejects_small_order_public_key() {
+        let witness = eddsa_witness(small_order_forgery());
+        assert!(evaluation_error(witness).starts_with("Unsatisfiable constraint"));
+    }
+
+    // Over three million constraints, so it only runs with the `slow-tests` feature.
+    #[cfg(feature = "slow-tests")]
+    #[test]
+    fn test_ecdsa_operation_satisfies_constraints() {
+        use crate::utils::secp256k1::secp256k1::ecdsa_operands;
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        let names: Vec<String> = ["z", "r", "s", "x", "y"]
+            .iter()
+            .flat_map(|name| (0..4).map(move |limb| format!("{}{}", name, limb)))
+            .collect();
+        let (circuit, r1cs) =
+            build(&serde_json::json!({"operation": "ECDSAVerify", "operands": names}).to_string());
+
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let prehash = [7u8; 32];
+        let signature: Signature = key.sign_prehash(&prehash).unwrap();
+        let mut inputs = assignment(&[]);
+        for (name, value) in
+            names
+                .iter()
+                .zip(ecdsa_operands(key.verifying_key(), &prehash, &signature))
+        {
+            inputs.insert(name.clone(), value);
+        }
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        assert_eq!(witness["out"], one());
+        assert!(r1cs.is_satisfied(&witness));
+
+        // A different message under the same signature.
+        let mut tampered = witness.clone();
+        tampered.insert("z0".to_string(), witness["z0"] + one());
+        assert!(!r1cs.is_satisfied(&tampered));
+    }
+
+    #[test]
+    fn test_fixed_point_pricing() {
+        // Is 19.99 per unit times the quantity under a budget of 50?
+        let (circuit, r1cs) = build(
+            r#"{"operation": "LessThan",
+                "operands": [
+                    {"operation": {"FixedMul": {"scale": 16}},
+                     "operands": [{"fixed": "19.99", "scale": 16}, "quantity"]},
+                    {"fixed": "50", "scale": 16}]}"#,
+        );
+        let total = match &circuit.operands[0] {
+            Operand::NestedCircuit(product) => product.hash.to_string(),
+            _ => unreachable!(),
+        };
+
+        let inputs = parse_inputs(r#"{"quantity": {"fixed": "2.5", "scale": 16}}"#).unwrap();
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        assert_eq!(witness["out"], one());
+        assert!(r1cs.is_satisfied(&witness));
+        assert_eq!(decode(&witness[&total], 16), "49.9749755859375");
+
+        let inputs = parse_inputs(r#"{"quantity": {"fixed": "2.6", "scale": 16}}"#).unwrap();
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        assert_eq!(witness["out"], FieldElement::from(0));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_typed_operations_wrap_and_range_check_inputs() {
+        // (a + b mod 2^32) XOR (a >> 4)
+        let (circuit, r1cs) = build(
+            r#"{"operation": {"Xor": {"type": "u32"}},
+                "operands": [
+                    {"operation": {"WrappingAdd": {"type": "u32"}}, "operands": ["a", "b"]},
+                    {"operation": {"ShiftRight": {"type": "u32", "amount": 4}}, "operands": ["a"]}],
+                "types": {"a": "u32", "b": "u32", "flag": "bool"}}"#,
+        );
+        for index in 0..32 {
+            let name = Circuit::type_variable("a", index);
+            assert!(r1cs.variable_map.get_index(&name).is_some());
+        }
+
+        let (a, b) = (0xffff_fff0u32, 0x20u32);
+        let inputs = assignment(&[("a", a as i64), ("b", b as i64), ("flag", 1)]);
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        let expected = a.wrapping_add(b) ^ (a >> 4);
+        assert_eq!(witness["out"], FieldElement::from(expected));
+        assert!(r1cs.is_satisfied(&witness));
+
+        let mut tampered = witness.clone();
+        tampered.insert(Circuit::type_variable("a", 0), one() + one());
+        assert!(!r1cs.is_satisfied(&tampered));
+    }
+
+    #[test]
+    fn test_typed_input_rejects_out_of_range_value() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Multiply", "operands": ["flag", "x"], "types": {"flag": "bool"}}"#,
+        );
+        let witness = r1cs.compute_witness(&circuit, assignment(&[("flag", 2), ("x", 5)]));
+        assert_eq!(
+            evaluation_error(witness),
+            "Input flag = 2 is out of range for Bool"
+        );
+
+        let witness = r1cs.compute_witness(&circuit, assignment(&[("x", 5)]));
+        assert_eq!(evaluation_error(witness), "Missing input variable flag");
+    }
+
+    const TYPED_SUM: &str = r#"{"operation": "Multiply",
+        "operands": [{"operation": "Add", "operands": ["x", "y"], "type": "u8"}, 2]}"#;
+
+    #[test]
+    fn test_typed_intermediate_is_range_checked() {
+        let (circuit, r1cs) = build(TYPED_SUM);
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 200), ("y", 55)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(510));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_typed_intermediate_rejects_overflow() {
+        let (circuit, r1cs) = build(TYPED_SUM);
+        let witness = r1cs.compute_witness(&circuit, assignment(&[("x", 200), ("y", 56)]));
+        assert_eq!(
+            evaluation_error(witness),
+            "Value 256 is out of range for U8"
+        );
+    }
+
+    const ARRAYS: &str = r#"{"operation": "Add",
+        "operands": [
+            {"operation": "Dot", "operands": ["v", "w"]},
+            {"operation": "Sum",
+             "operands": [{"operation": {"Map": {"operation": "Multiply"}}, "operands": ["v", 2]},
+                          "w[2]"]}],
+        "arrays": {"v": 3, "w": 3},
+        "public_inputs": ["w"]}"#;
+
+    #[test]
+    fn test_array_operations_expand_to_elements() {
+        let (circuit, r1cs) = build(ARRAYS);
+        for (index, name) in ["1", "w[0]", "w[1]", "w[2]", "v[0]"].iter().enumerate() {
+            assert_eq!(r1cs.variable_map.get_item(index).unwrap(), name);
+        }
+
+        let inputs = crate::circuits::parse_inputs(r#"{"v": [1, 2, 3], "w": [4, 5, 6]}"#).unwrap();
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        // v.w + 2 (v[0] + v[1] + v[2]) + w[2]
+        assert_eq!(witness["out"], FieldElement::from(32 + 12 + 6));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index 3 is out of bounds for array v of length 3")]
+    fn test_array_index_out_of_bounds() {
+        build(&ARRAYS.replace("w[2]", "v[3]"));
+    }
+
+    #[test]
+    fn test_hints_are_checked_by_separate_constraints() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Add",
+                "operands": [
+                    {"operation": "AssertEqual",
+                     "operands": [{"operation": "Multiply",
+                                   "operands": ["x", {"operation": {"Hint": {"name": "inverse"}},
+                                                      "operands": ["x"]}]},
+                                  1]},
+                    {"operation": "AssertEqual",
+                     "operands": [{"operation": {"Pow": {"exponent": 2}},
+                                   "operands": [{"operation": {"Hint": {"name": "sqrt"}},
+                                                 "operands": ["x"]}]},
+                                  "x"]}]}"#,
+        );
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 16)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(1 + 16));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_division_with_remainder_hint() {
+        let quotient = r#"{"operation": {"Hint": {"name": "divmod"}}, "operands": ["a", "b"]}"#;
+        let remainder = quotient.replace(r#""name": "divmod""#, r#""name": "divmod", "output": 1"#);
+        let (circuit, r1cs) = build(&format!(
+            r#"{{"operation": "AssertEqual",
+                "operands": [{{"operation": "Add",
+                               "operands": [{{"operation": "Multiply", "operands": [{}, "b"]}},
+                                            {{"operation": {{"RangeCheck": {{"bits": 3}}}},
+                                              "operands": [{}]}}]}},
+                             "a"]}}"#,
+            quotient, remainder
+        ));
+
+        let mut witness = r1cs
+            .compute_witness(&circuit, assignment(&[("a", 23), ("b", 7)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(23));
+        assert!(r1cs.is_satisfied(&witness));
+
+        // Another quotient satisfies no constraints once the hint is tampered with.
+        let Operand::NestedCircuit(sum) = &circuit.operands[0] else {
+            unreachable!()
+        };
+        let Operand::NestedCircuit(product) = &sum.operands[0] else {
+            unreachable!()
+        };
+        let Operand::NestedCircuit(hint) = &product.operands[0] else {
+            unreachable!()
+        };
+        witness.insert(hint.hash.to_string(), FieldElement::from(2));
+        assert!(!r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_registered_hint() {
+        let (circuit, mut r1cs) =
+            build(r#"{"operation": {"Hint": {"name": "square"}}, "operands": ["x"]}"#);
+        r1cs.register_hint("square", |inputs| Ok(vec![inputs[0] * inputs[0]]));
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 10)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(100));
+    }
+
+    #[test]
+    fn test_lookup_table_of_two_bit_xor() {
+        let rows: Vec<String> = (0..16)
+            .map(|i| format!("[{}, {}, {}]", i / 4, i % 4, (i / 4) ^ (i % 4)))
+            .collect();
+        let (circuit, r1cs) = build(&format!(
+            r#"{{"operation": {{"Lookup": {{"table": [{}]}}}}, "operands": ["a", "b"]}}"#,
+            rows.join(", ")
+        ));
+        assert_eq!(r1cs.num_constraints(), 16 + 1 + 2 + 1);
+
+        let mut witness = r1cs
+            .compute_witness(&circuit, assignment(&[("a", 2), ("b", 3)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(1));
+        assert!(r1cs.is_satisfied(&witness));
+
+        // Selecting another row contradicts the operands.
+        witness.insert(
+            Circuit::auxiliary_variable(circuit.hash, 11),
+            FieldElement::from(0),
+        );
+        witness.insert(
+            Circuit::auxiliary_variable(circuit.hash, 10),
+            FieldElement::from(1),
+        );
+        assert!(!r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_deep_circuit_is_compiled_and_evaluated_without_recursion() {
+        let depth = 100_000;
+        let json = format!(
+            r#"{}"x"{}"#,
+            r#"{"operation": "Add", "operands": ["#.repeat(depth),
+            r#", 1]}"#.repeat(depth)
+        );
+        let mut circuit = Circuit::from_json(&json).unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        assert_eq!(r1cs.num_constraints(), depth);
+
+        let witness = r1cs
+            .compute_witness(&circuit, assignment(&[("x", 1)]))
+            .unwrap();
+        assert_eq!(witness["out"], FieldElement::from(depth as u64 + 1));
+        assert!(r1cs.is_satisfied(&witness));
+    }
+
+    #[test]
+    fn test_witness_vector_is_aligned_with_variable_map() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Multiply",
+                "operands": [{"operation": {"ToBits": {"n": 3}}, "operands": ["x"]}, "y"],
+                "public_inputs": ["y"]}"#,
+        );
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(5)),
+            ("y".to_string(), FieldElement::from(4)),
+        ]);
+
+        let vector = r1cs
+            .compute_witness_vector(&circuit, inputs.clone())
+            .unwrap();
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        assert_eq!(vector.len(), r1cs.variable_map.len());
+        assert_eq!(vector[0], one());
+        for (index, value) in vector.iter().enumerate() {
+            assert_eq!(value, &witness[r1cs.variable_map.get_item(index).unwrap()]);
+        }
+        assert!(r1cs.is_satisfied_by_vector(&vector));
+    }
+
+    #[test]
+    fn test_counts_public_inputs_listed_out_of_order() {
+        let (circuit, r1cs) = build(
+            r#"{"operation": "Add", "operands": ["x", "y"], "public_inputs": ["y", "x", "y"]}"#,
+        );
+        assert_eq!((r1cs.num_public_inputs, r1cs.num_private_inputs), (2, 1));
+        let vector = r1cs
+            .compute_witness_vector(&circuit, assignment(&[("x", 3), ("y", 4)]))
+            .unwrap();
+        assert_eq!(
+            vector[1..=2],
+            [FieldElement::from(4), FieldElement::from(3)]
+        );
+
+        let (_, r1cs) = build(
+            r#"{"operation": "Add", "operands": ["x", 1], "public_inputs": ["x", "x", "x"]}"#,
+        );
+        assert_eq!((r1cs.num_public_inputs, r1cs.num_private_inputs), (1, 1));
+    }
+
+    #[test]
+    fn test_witness_vector_reports_missing_slot() {
+        let (circuit, r1cs) =
+            build(r#"{"operation": "Add", "operands": ["x", 1], "public_inputs": ["unused"]}"#);
+        match r1cs.compute_witness_vector(&circuit, assignment(&[("x", 2)])) {
+            Err(CircuitError::EvaluationError(message)) => {
+                assert_eq!(message, "Missing value for witness slot 1 (unused)")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_witness_rejects_constant_input_and_missing_operand() {
+        let (circuit, r1cs) = build(r#"{"operation": "Add", "operands": ["x", "y"]}"#);
+
+        let witness = r1cs.compute_witness(&circuit, assignment(&[("1", 2), ("x", 1), ("y", 1)]));
+        assert_eq!(
+            evaluation_error(witness),
+            "Input 1 is reserved for the constant"
+        );
+        match r1cs.compute_witness_vector(&circuit, assignment(&[("x", 1)])) {
+            Err(CircuitError::EvaluationError(message)) => {
+                assert_eq!(message, "Missing input variable y")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
     }
 }
@@ -0,0 +1,919 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::thread;
+
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::circuits::{square_and_multiply_chain, Circuit, CircuitError, Operand, Operation, Type};
+use crate::gadgets::{ConstraintSystem, Signal};
+use crate::r1cs::r1cs::R1CS;
+use crate::utils::field::field::{bit_length, FieldElement};
+use crate::utils::hints::hints::Hint;
+use crate::utils::mimc::mimc::{feistel_constants, round_constants};
+use crate::utils::poseidon::poseidon::{parameters, PoseidonParameters};
+
+/// Operand of an instruction: a witness slot or a constant.
+#[derive(Debug, Clone, Copy)]
+pub enum Term {
+    Slot(usize),
+    Constant(FieldElement),
+}
+
+/// Step of a witness computation, writing the slots of one circuit's output and auxiliary
+/// variables.
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Add {
+        a: Term,
+        b: Term,
+        out: usize,
+    },
+    Multiply {
+        a: Term,
+        b: Term,
+        out: usize,
+    },
+    Select {
+        condition: Term,
+        a: Term,
+        b: Term,
+        out: usize,
+    },
+    /// Square-and-multiply chain of the exponent, computed when compiling, whose intermediate
+    /// results go to `auxiliary`.
+    Pow {
+        base: Term,
+        exponent: u64,
+        chain: Vec<bool>,
+        auxiliary: Vec<usize>,
+        out: usize,
+    },
+    /// Decomposition of the value into little-endian `bits`, copying it to `out`.
+    Bits {
+        value: Term,
+        bits: Vec<usize>,
+        out: usize,
+    },
+    Hint {
+        hint: Hint,
+        inputs: Vec<Term>,
+        output: usize,
+        out: usize,
+    },
+    Sum {
+        inputs: Vec<Term>,
+        out: usize,
+    },
+    /// Dot product of `a` and `b`, whose element-wise products go to `products`.
+    Dot {
+        a: Vec<Term>,
+        b: Vec<Term>,
+        products: Vec<usize>,
+        out: usize,
+    },
+    AssertEqual {
+        a: Term,
+        b: Term,
+        out: usize,
+    },
+    /// Row lookup in a table converted to field elements when compiling, setting the selector
+    /// of the matching row in `selectors`.
+    Lookup {
+        table: Vec<Vec<FieldElement>>,
+        inputs: Vec<Term>,
+        selectors: Vec<usize>,
+        out: usize,
+    },
+    /// MiMC-7 with its round constants computed when compiling, writing the four products of
+    /// each round to `auxiliary`.
+    MiMC {
+        constants: Vec<FieldElement>,
+        x: Term,
+        key: Term,
+        auxiliary: Vec<usize>,
+        out: usize,
+    },
+    /// Left half of the MiMC Feistel permutation with its round constants computed when
+    /// compiling, writing the three products of each round to `auxiliary`.
+    MiMCFeistel {
+        constants: Vec<FieldElement>,
+        left: Term,
+        right: Term,
+        key: Term,
+        auxiliary: Vec<usize>,
+        out: usize,
+    },
+    /// Poseidon hash with the parameters of its width, fetched when compiling, writing the
+    /// three products of each S-box to `auxiliary`.
+    Poseidon {
+        parameters: &'static PoseidonParameters,
+        inputs: Vec<Term>,
+        auxiliary: Vec<usize>,
+        out: usize,
+    },
+    /// Operation lowered through its gadget, run on values only.
+    Gadget {
+        operation: Operation,
+        inputs: Vec<Term>,
+        auxiliary: Vec<usize>,
+        out: usize,
+    },
+    /// Range check of the value in `slot` against its declared type, decomposing it into the
+    /// little-endian bits in `auxiliary` for integer types.
+    Type {
+        ty: Type,
+        slot: usize,
+        auxiliary: Vec<usize>,
+    },
+}
+
+/// Witness computation of a circuit compiled to instructions over the slots of the witness
+/// vector, ordered as the R1CS variable map.
+///
+/// Executing the tape performs no lookup by name. Only `Gadget` instructions run gadget code,
+/// the other instructions computing their values natively.
+#[derive(Debug, Clone)]
+pub struct Tape {
+    size: usize,
+    inputs: Vec<usize>,
+    instructions: Vec<Instruction>,
+}
+
+impl Tape {
+    /// Slots of the input variables, in the order `execute` expects their values.
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Computes the witness vector from the values of the inputs, the constant slot 0 being
+    /// set to 1. Fails with an `EvaluationError` when an input is out of range for its type or
+    /// an operation is undefined on its operands.
+    pub fn execute(&self, inputs: &[FieldElement]) -> Result<Vec<FieldElement>, CircuitError> {
+        if inputs.len() != self.inputs.len() {
+            return Err(evaluation_error(format!(
+                "Expected {} input values, found {}",
+                self.inputs.len(),
+                inputs.len()
+            )));
+        }
+
+        let mut witness = vec![FieldElement::from(0); self.size];
+        witness[0] = FieldElement::from(1);
+        for (&slot, &value) in self.inputs.iter().zip(inputs) {
+            witness[slot] = value;
+        }
+        for instruction in &self.instructions {
+            execute(instruction, &mut witness)?;
+        }
+        Ok(witness)
+    }
+}
+
+fn evaluation_error(message: String) -> CircuitError {
+    CircuitError::EvaluationError(message)
+}
+
+fn execute(instruction: &Instruction, witness: &mut [FieldElement]) -> Result<(), CircuitError> {
+    let value = |witness: &[FieldElement], term: &Term| match *term {
+        Term::Slot(slot) => witness[slot],
+        Term::Constant(constant) => constant,
+    };
+
+    match instruction {
+        Instruction::Add { a, b, out } => witness[*out] = value(witness, a) + value(witness, b),
+        Instruction::Multiply { a, b, out } => {
+            witness[*out] = value(witness, a) * value(witness, b)
+        }
+        Instruction::Select {
+            condition,
+            a,
+            b,
+            out,
+        } => {
+            let condition = value(witness, condition);
+            if condition != FieldElement::from(0) && condition != FieldElement::from(1) {
+                return Err(evaluation_error(format!(
+                    "Select condition must be boolean, found {}",
+                    condition
+                )));
+            }
+            let b = value(witness, b);
+            witness[*out] = b + condition * (value(witness, a) - b);
+        }
+        Instruction::Pow {
+            base,
+            exponent,
+            chain,
+            auxiliary,
+            out,
+        } => {
+            let base = value(witness, base);
+            let mut accumulator = if *exponent == 0 {
+                FieldElement::from(1)
+            } else {
+                base
+            };
+            for (step, &multiply) in chain.iter().enumerate() {
+                accumulator *= if multiply { base } else { accumulator };
+                if let Some(&slot) = auxiliary.get(step) {
+                    witness[slot] = accumulator;
+                }
+            }
+            witness[*out] = accumulator;
+        }
+        Instruction::Bits {
+            value: operand,
+            bits,
+            out,
+        } => {
+            let operand = value(witness, operand);
+            if bit_length(&operand) > bits.len() {
+                return Err(evaluation_error(format!(
+                    "Value {} does not fit in {} bits",
+                    operand,
+                    bits.len()
+                )));
+            }
+            let integer = operand.into_bigint();
+            for (i, &slot) in bits.iter().enumerate() {
+                witness[slot] = FieldElement::from(integer.get_bit(i));
+            }
+            witness[*out] = operand;
+        }
+        Instruction::Hint {
+            hint,
+            inputs,
+            output,
+            out,
+        } => {
+            let inputs: Vec<FieldElement> =
+                inputs.iter().map(|term| value(witness, term)).collect();
+            let advice = hint(&inputs)?;
+            witness[*out] = *advice.get(*output).ok_or_else(|| {
+                evaluation_error(format!(
+                    "Hint computed {} values, found output {}",
+                    advice.len(),
+                    output
+                ))
+            })?;
+        }
+        Instruction::Sum { inputs, out } => {
+            witness[*out] = inputs.iter().map(|term| value(witness, term)).sum();
+        }
+        Instruction::Dot {
+            a,
+            b,
+            products,
+            out,
+        } => {
+            let mut sum = FieldElement::from(0);
+            for ((a, b), &slot) in a.iter().zip(b).zip(products) {
+                witness[slot] = value(witness, a) * value(witness, b);
+                sum += witness[slot];
+            }
+            witness[*out] = sum;
+        }
+        Instruction::AssertEqual { a, b, out } => {
+            let (a, b) = (value(witness, a), value(witness, b));
+            if a != b {
+                return Err(evaluation_error(format!(
+                    "Unsatisfiable constraint: {} != {}",
+                    a, b
+                )));
+            }
+            witness[*out] = a;
+        }
+        Instruction::Lookup {
+            table,
+            inputs,
+            selectors,
+            out,
+        } => {
+            let values: Vec<FieldElement> =
+                inputs.iter().map(|term| value(witness, term)).collect();
+            let width = values.len();
+            let row = table
+                .iter()
+                .position(|row| row.starts_with(&values))
+                .ok_or_else(|| {
+                    evaluation_error(format!(
+                        "Unsatisfiable constraint: {:?} is not a row of the lookup table",
+                        values.iter().map(|x| x.to_string()).collect::<Vec<_>>()
+                    ))
+                })?;
+            for (i, &slot) in selectors.iter().enumerate() {
+                witness[slot] = FieldElement::from(i == row);
+            }
+            witness[*out] = table[row]
+                .get(width)
+                .copied()
+                .unwrap_or(FieldElement::from(1));
+        }
+        Instruction::MiMC {
+            constants,
+            x,
+            key,
+            auxiliary,
+            out,
+        } => {
+            let key = value(witness, key);
+            let mut state = value(witness, x);
+            for (constant, slots) in constants.iter().zip(auxiliary.chunks(4)) {
+                let base = state + key + constant;
+                let square = base * base;
+                let fourth = square * square;
+                let sixth = fourth * square;
+                state = sixth * base;
+                for (&slot, value) in slots.iter().zip([square, fourth, sixth, state]) {
+                    witness[slot] = value;
+                }
+            }
+            witness[*out] = state + key;
+        }
+        Instruction::MiMCFeistel {
+            constants,
+            left,
+            right,
+            key,
+            auxiliary,
+            out,
+        } => {
+            let key = value(witness, key);
+            let (mut left, mut right) = (value(witness, left), value(witness, right));
+            for (round, (constant, slots)) in constants.iter().zip(auxiliary.chunks(3)).enumerate()
+            {
+                let base = left + key + constant;
+                let square = base * base;
+                let fourth = square * square;
+                let fifth = fourth * base;
+                for (&slot, value) in slots.iter().zip([square, fourth, fifth]) {
+                    witness[slot] = value;
+                }
+
+                let mixed = right + fifth;
+                if round + 1 < constants.len() {
+                    right = left;
+                    left = mixed;
+                } else {
+                    right = mixed;
+                }
+            }
+            witness[*out] = left;
+        }
+        Instruction::Poseidon {
+            parameters,
+            inputs,
+            auxiliary,
+            out,
+        } => {
+            let width = parameters.width;
+            let mut state = vec![FieldElement::from(0)];
+            state.extend(inputs.iter().map(|term| value(witness, term)));
+
+            let mut slots = auxiliary.chunks(3);
+            for round in 0..parameters.rounds() {
+                for (element, constant) in state
+                    .iter_mut()
+                    .zip(&parameters.round_constants[round * width..])
+                {
+                    *element += constant;
+                }
+
+                let sbox_count = if parameters.is_full_round(round) {
+                    width
+                } else {
+                    1
+                };
+                for element in &mut state[..sbox_count] {
+                    let square = *element * *element;
+                    let fourth = square * square;
+                    *element *= fourth;
+                    let slots = slots.next().expect("Missing slots for Poseidon S-box");
+                    for (&slot, value) in slots.iter().zip([square, fourth, *element]) {
+                        witness[slot] = value;
+                    }
+                }
+
+                state = parameters
+                    .mds
+                    .iter()
+                    .map(|row| row.iter().zip(&state).map(|(&m, &s)| m * s).sum())
+                    .collect();
+            }
+            witness[*out] = state[0];
+        }
+        Instruction::Gadget {
+            operation,
+            inputs,
+            auxiliary,
+            out,
+        } => {
+            let inputs: Vec<Signal> = inputs
+                .iter()
+                .map(|term| Signal::from_value(value(witness, term)))
+                .collect();
+            let mut writer = SlotWriter::new(witness, auxiliary);
+            let result = operation.synthesize(&mut writer, &inputs);
+            if let Some(error) = writer.error {
+                return Err(evaluation_error(error));
+            }
+            witness[*out] = result
+                .and_then(|signal| signal.value)
+                .expect("Operation has no gadget");
+        }
+        Instruction::Type {
+            ty,
+            slot,
+            auxiliary,
+        } => {
+            let value = witness[*slot];
+            if !ty.contains(&value) {
+                return Err(evaluation_error(format!(
+                    "Value {} is out of range for {:?}",
+                    value, ty
+                )));
+            }
+            let integer = value.into_bigint();
+            for (i, &slot) in auxiliary.iter().enumerate() {
+                witness[slot] = FieldElement::from(integer.get_bit(i));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Records the values of a gadget's auxiliary variables in their slots, and the first
+/// constraint they fail to satisfy.
+struct SlotWriter<'a> {
+    witness: &'a mut [FieldElement],
+    slots: &'a [usize],
+    next: usize,
+    error: Option<String>,
+}
+
+impl<'a> SlotWriter<'a> {
+    fn new(witness: &'a mut [FieldElement], slots: &'a [usize]) -> Self {
+        SlotWriter {
+            witness,
+            slots,
+            next: 0,
+            error: None,
+        }
+    }
+}
+
+impl ConstraintSystem for SlotWriter<'_> {
+    fn allocate(&mut self, value: Option<FieldElement>) -> Signal {
+        let value = value.expect("Missing value for auxiliary variable");
+        self.witness[self.slots[self.next]] = value;
+        self.next += 1;
+        Signal::from_value(value)
+    }
+
+    fn enforce(&mut self, a: &Signal, b: &Signal, c: &Signal) {
+        if let (Some(a), Some(b), Some(c)) = (a.value, b.value, c.value) {
+            if a * b != c && self.error.is_none() {
+                self.error = Some(format!("Unsatisfiable constraint: {} * {} != {}", a, b, c));
+            }
+        }
+    }
+}
+
+impl R1CS {
+    /// Compiles the witness computation of an indexed circuit into a tape over the slots of
+    /// this R1CS's variable map, failing with an `EvaluationError` on an unknown hint.
+    pub fn compile(&self, circuit: &Circuit) -> Result<Tape, CircuitError> {
+        let slot = |name: &String| -> usize {
+            self.variable_map
+                .get_index(name)
+                .unwrap_or_else(|| panic!("Cannot find index of {} in variable_indices", name))
+        };
+        let auxiliary = |hash: u64, indices: std::ops::Range<usize>| -> Vec<usize> {
+            indices
+                .map(|index| slot(&Circuit::auxiliary_variable(hash, index)))
+                .collect()
+        };
+
+        let mut instructions = Vec::new();
+        let mut inputs: BTreeSet<usize> = circuit.public_inputs.iter().map(slot).collect();
+        for (input, ty) in &circuit.types {
+            inputs.insert(slot(input));
+            instructions.push(Instruction::Type {
+                ty: *ty,
+                slot: slot(input),
+                auxiliary: (0..ty.auxiliary_count())
+                    .map(|index| slot(&Circuit::type_variable(input, index)))
+                    .collect(),
+            });
+        }
+
+        // Circuits are emitted after their operands, once each however often they occur.
+        let mut emitted = HashSet::new();
+        let mut stack = vec![(circuit, Vec::with_capacity(circuit.operands.len()))];
+        while let Some((node, terms)) = stack.last_mut() {
+            let node = *node;
+            match node.operands.get(terms.len()) {
+                Some(Operand::NestedCircuit(nested)) if !emitted.contains(&nested.hash) => {
+                    stack.push((nested, Vec::with_capacity(nested.operands.len())));
+                }
+                Some(operand) => terms.push(match operand {
+                    Operand::Number(num) => Term::Constant(FieldElement::from(*num)),
+                    Operand::Fixed(fixed) => Term::Constant(fixed.encode()),
+                    Operand::Variable(var) => {
+                        inputs.insert(slot(var));
+                        Term::Slot(slot(var))
+                    }
+                    Operand::NestedCircuit(nested) => Term::Slot(slot(&nested.hash.to_string())),
+                }),
+                None => {
+                    let (node, terms) = stack.pop().unwrap();
+                    let hash = node.hash;
+                    let out = if stack.is_empty() {
+                        slot(&"out".to_string())
+                    } else {
+                        slot(&hash.to_string())
+                    };
+                    let operation_count = node.operation.auxiliary_count(terms.len());
+
+                    instructions.push(match node.operation {
+                        Operation::Add => Instruction::Add {
+                            a: terms[0],
+                            b: terms[1],
+                            out,
+                        },
+                        Operation::Multiply => Instruction::Multiply {
+                            a: terms[0],
+                            b: terms[1],
+                            out,
+                        },
+                        Operation::Select => Instruction::Select {
+                            condition: terms[0],
+                            a: terms[1],
+                            b: terms[2],
+                            out,
+                        },
+                        Operation::Pow { exponent } => Instruction::Pow {
+                            base: terms[0],
+                            exponent,
+                            chain: square_and_multiply_chain(exponent),
+                            auxiliary: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                        Operation::ToBits { n: bits } | Operation::RangeCheck { bits } => {
+                            Instruction::Bits {
+                                value: terms[0],
+                                bits: auxiliary(hash, 0..bits),
+                                out,
+                            }
+                        }
+                        Operation::Hint { ref name, output } => Instruction::Hint {
+                            hint: self.hints().get(name)?,
+                            inputs: terms,
+                            output,
+                            out,
+                        },
+                        Operation::Sum => Instruction::Sum { inputs: terms, out },
+                        Operation::Dot => {
+                            let (a, b) = terms.split_at(terms.len() / 2);
+                            Instruction::Dot {
+                                a: a.to_vec(),
+                                b: b.to_vec(),
+                                products: auxiliary(hash, 0..operation_count),
+                                out,
+                            }
+                        }
+                        Operation::AssertEqual => Instruction::AssertEqual {
+                            a: terms[0],
+                            b: terms[1],
+                            out,
+                        },
+                        Operation::Lookup { ref table } => Instruction::Lookup {
+                            table: table
+                                .iter()
+                                .map(|row| row.iter().map(|&x| FieldElement::from(x)).collect())
+                                .collect(),
+                            inputs: terms,
+                            selectors: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                        Operation::MiMC { rounds } => Instruction::MiMC {
+                            constants: round_constants(rounds),
+                            x: terms[0],
+                            key: terms[1],
+                            auxiliary: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                        Operation::MiMCFeistel { rounds } => Instruction::MiMCFeistel {
+                            constants: feistel_constants(rounds),
+                            left: terms[0],
+                            right: terms[1],
+                            key: terms[2],
+                            auxiliary: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                        Operation::Poseidon => Instruction::Poseidon {
+                            parameters: parameters(terms.len() + 1),
+                            inputs: terms,
+                            auxiliary: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                        ref operation => Instruction::Gadget {
+                            operation: operation.clone(),
+                            inputs: terms,
+                            auxiliary: auxiliary(hash, 0..operation_count),
+                            out,
+                        },
+                    });
+                    if let Some(ty) = node.ty {
+                        let first = operation_count;
+                        instructions.push(Instruction::Type {
+                            ty,
+                            slot: out,
+                            auxiliary: auxiliary(hash, first..first + ty.auxiliary_count()),
+                        });
+                    }
+
+                    emitted.insert(hash);
+                    if let Some((_, terms)) = stack.last_mut() {
+                        terms.push(Term::Slot(out));
+                    }
+                }
+            }
+        }
+
+        Ok(Tape {
+            size: self.variable_map.len(),
+            inputs: inputs.into_iter().collect(),
+            instructions,
+        })
+    }
+
+    /// Computes the witness vectors of many input sets, compiling the circuit once and letting
+    /// threads pull the input sets from the iterator as they go. Results are in the order of
+    /// the input sets, an input set with a missing or invalid value yielding an
+    /// `EvaluationError`. Fails as a whole when the circuit does not compile.
+    pub fn compute_witnesses(
+        &self,
+        circuit: &Circuit,
+        inputs: impl Iterator<Item = HashMap<String, FieldElement>> + Send,
+    ) -> Result<Vec<Result<Vec<FieldElement>, CircuitError>>, CircuitError> {
+        let tape = self.compile(circuit)?;
+        let names: Vec<&String> = tape
+            .inputs()
+            .iter()
+            .map(|&slot| self.variable_map.get_item(slot).unwrap())
+            .collect();
+        let execute = |inputs: HashMap<String, FieldElement>| {
+            let values = names
+                .iter()
+                .map(|&name| {
+                    inputs
+                        .get(name)
+                        .copied()
+                        .ok_or_else(|| evaluation_error(format!("Missing input variable {}", name)))
+                })
+                .collect::<Result<Vec<FieldElement>, CircuitError>>()?;
+            tape.execute(&values)
+        };
+
+        let inputs = Mutex::new(inputs.enumerate());
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            // The lock is released before executing the tape.
+                            let next = inputs.lock().unwrap().next();
+                            let Some((index, inputs)) = next else {
+                                return results;
+                            };
+                            results.push((index, execute(inputs)));
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        results.sort_unstable_by_key(|&(index, _)| index);
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_tape_matches_tree_evaluation() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Sum",
+                "operands": [
+                    {"operation": {"WrappingAdd": {"type": "u8"}},
+                     "operands": ["a", {"operation": {"Pow": {"exponent": 5}}, "operands": ["b"]}]},
+                    {"operation": "Select",
+                     "operands": ["c",
+                                  {"operation": "Poseidon", "operands": ["a", "b"]},
+                                  {"operation": {"ToBits": {"n": 8}},
+                                   "operands": [{"operation": "Add", "operands": ["a", 1]}]}]},
+                    {"operation": {"Hint": {"name": "inverse"}}, "operands": ["b"]},
+                    {"operation": "Add", "operands": ["a", 1]}],
+                "types": {"a": "u8"},
+                "public_inputs": ["c"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let tape = r1cs.compile(&circuit).unwrap();
+
+        let names: Vec<&String> = tape
+            .inputs()
+            .iter()
+            .map(|&slot| r1cs.variable_map.get_item(slot).unwrap())
+            .collect();
+        assert_eq!(names, ["c", "a", "b"]);
+
+        for (a, b, c) in [(200, 3, 1), (7, 2, 0)] {
+            let values = [c, a, b].map(FieldElement::from);
+            let witness = tape.execute(&values).unwrap();
+
+            let inputs = HashMap::from([
+                ("a".to_string(), values[1]),
+                ("b".to_string(), values[2]),
+                ("c".to_string(), values[0]),
+            ]);
+            let expected = r1cs.compute_witness(&circuit, inputs).unwrap();
+            for (slot, value) in witness.iter().enumerate() {
+                let name = r1cs.variable_map.get_item(slot).unwrap();
+                assert_eq!(value, &expected[name], "slot {} ({})", slot, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_native_instructions_run_no_gadget() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Sum",
+                "operands": [
+                    {"operation": "Dot", "operands": ["a", "b", "c", "d"]},
+                    {"operation": "AssertEqual",
+                     "operands": [{"operation": {"Lookup": {"table": [[1, 1], [2, 4], [3, 9]]}},
+                                   "operands": ["a"]},
+                                  "e"]},
+                    {"operation": {"MiMC": {"rounds": 91}}, "operands": ["a", "b"]},
+                    {"operation": {"MiMCFeistel": {"rounds": 220}}, "operands": ["a", "b", "c"]},
+                    {"operation": "Poseidon", "operands": ["a", "b", "c"]}]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let tape = r1cs.compile(&circuit).unwrap();
+        assert!(!tape
+            .instructions()
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Gadget { .. })));
+
+        let inputs = HashMap::from(
+            [("a", 2), ("b", 5), ("c", 7), ("d", 11), ("e", 4)]
+                .map(|(name, value)| (name.to_string(), FieldElement::from(value))),
+        );
+        let values: Vec<FieldElement> = tape
+            .inputs()
+            .iter()
+            .map(|&slot| inputs[r1cs.variable_map.get_item(slot).unwrap()])
+            .collect();
+        let witness = tape.execute(&values).unwrap();
+        let expected = r1cs.compute_witness(&circuit, inputs).unwrap();
+        for (slot, value) in witness.iter().enumerate() {
+            let name = r1cs.variable_map.get_item(slot).unwrap();
+            assert_eq!(value, &expected[name], "slot {} ({})", slot, name);
+        }
+    }
+
+    #[test]
+    fn test_tape_rejects_out_of_range_input() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Add", "operands": ["a", 1], "types": {"a": "u8"}}"#,
+        )
+        .unwrap();
+        let r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        match r1cs
+            .compile(&circuit)
+            .unwrap()
+            .execute(&[FieldElement::from(256)])
+        {
+            Err(CircuitError::EvaluationError(message)) => {
+                assert_eq!(message, "Value 256 is out of range for U8")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_failing_hints_and_gadgets_are_errors() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Add",
+                "operands": [{"operation": {"Hint": {"name": "sqrt"}}, "operands": ["x"]},
+                             {"operation": {"Lookup": {"table": [[1, 1], [2, 4]]}},
+                              "operands": ["y"]}]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+
+        let inputs = [(4, 2), (5, 2), (4, 3)].map(|(x, y)| {
+            HashMap::from([
+                ("x".to_string(), FieldElement::from(x)),
+                ("y".to_string(), FieldElement::from(y)),
+            ])
+        });
+        let results = r1cs
+            .compute_witnesses(&circuit, inputs.into_iter())
+            .unwrap();
+        assert!(results[0].is_ok());
+        for (result, expected) in results[1..]
+            .iter()
+            .zip(["5 is not a square", "Unsatisfiable constraint"])
+        {
+            match result {
+                Err(CircuitError::EvaluationError(message)) => {
+                    assert!(message.contains(expected), "{}", message)
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_witnesses_are_ordered_with_errors_per_item() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Multiply",
+                "operands": [{"operation": {"Pow": {"exponent": 3}}, "operands": ["x"]}, "y"],
+                "types": {"x": "u8"}}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+
+        let inputs = (0..100).map(|i| {
+            let mut inputs = HashMap::from([("x".to_string(), FieldElement::from(i * 3))]);
+            if i != 42 {
+                inputs.insert("y".to_string(), FieldElement::from(i));
+            }
+            inputs
+        });
+        let results = r1cs.compute_witnesses(&circuit, inputs).unwrap();
+        assert_eq!(results.len(), 100);
+
+        let out = r1cs.variable_map.get_index(&"out".to_string()).unwrap();
+        for (i, result) in results.iter().enumerate() {
+            match (i, result) {
+                (42, Err(CircuitError::EvaluationError(message))) => {
+                    assert_eq!(message, "Missing input variable y")
+                }
+                (i, Err(CircuitError::EvaluationError(message))) if i * 3 > 255 => {
+                    assert!(message.contains("out of range for U8"))
+                }
+                (i, Ok(witness)) if i * 3 <= 255 => {
+                    let i = i as u64;
+                    assert_eq!(witness[out], FieldElement::from(i * i * i * 27 * i));
+                }
+                (i, result) => panic!("unexpected result for input set {}: {:?}", i, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_hint_is_an_error() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": {"Hint": {"name": "cube_root"}}, "operands": ["x"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([("x".to_string(), FieldElement::from(8))]);
+
+        for error in [
+            r1cs.compile(&circuit).err(),
+            r1cs.compute_witness(&circuit, inputs.clone()).err(),
+            r1cs.compute_witnesses(&circuit, [inputs].into_iter()).err(),
+        ] {
+            match error {
+                Some(CircuitError::EvaluationError(message)) => {
+                    assert_eq!(message, "Unknown hint cube_root")
+                }
+                error => panic!("unexpected error {:?}", error),
+            }
+        }
+    }
+}
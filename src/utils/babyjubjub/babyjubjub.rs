@@ -0,0 +1,156 @@
+use crate::utils::field::field::{to_bits_le, FieldElement};
+use ark_ff::fields::{Fp256, MontBackend};
+use ark_ff::{BigInteger, Field, PrimeField};
+use std::str::FromStr;
+
+/// Twisted Edwards coefficients of Baby Jubjub, `a x^2 + y^2 = 1 + d x^2 y^2` over the
+/// circuit's field.
+pub const A: u64 = 168700;
+pub const D: u64 = 168696;
+
+mod scalar {
+    // The derive expands to code tripping lints of newer compilers.
+    #![allow(unexpected_cfgs, non_local_definitions)]
+    use ark_ff::fields::MontConfig;
+
+    #[derive(MontConfig)]
+    #[modulus = "2736030358979909402780800718157159386076813972158567259200215660948447373041"]
+    #[generator = "31"]
+    pub struct ScalarConfig;
+}
+
+pub use self::scalar::ScalarConfig;
+
+/// Field of integers modulo the order of the prime subgroup generated by `Point::base8`.
+pub type Scalar = Fp256<MontBackend<ScalarConfig, 4>>;
+
+/// Bit length of the subgroup order.
+pub const SCALAR_BITS: usize = Scalar::MODULUS_BIT_SIZE as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: FieldElement,
+    pub y: FieldElement,
+}
+
+impl Point {
+    pub fn identity() -> Self {
+        Point {
+            x: FieldElement::from(0),
+            y: FieldElement::from(1),
+        }
+    }
+
+    /// Generator of the full curve group, of order `8 * l`.
+    pub fn generator() -> Self {
+        Point {
+            x: FieldElement::from_str(
+                "995203441582195749578291179787384436505546430278305826713579947235728471134",
+            )
+            .unwrap(),
+            y: FieldElement::from_str(
+                "5472060717959818805561601436314318772137091100104008585924551046643952123905",
+            )
+            .unwrap(),
+        }
+    }
+
+    /// `8 * generator()`, generating the prime order subgroup.
+    pub fn base8() -> Self {
+        Point {
+            x: FieldElement::from_str(
+                "5299619240641551281634865583518297030282874472190772894086521144482721001553",
+            )
+            .unwrap(),
+            y: FieldElement::from_str(
+                "16950150798460657717958625567821834550301663161624707787222815936182638968203",
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let (x2, y2) = (self.x.square(), self.y.square());
+        FieldElement::from(A) * x2 + y2 == FieldElement::from(1) + FieldElement::from(D) * x2 * y2
+    }
+
+    /// Complete twisted Edwards addition.
+    pub fn add(&self, other: &Point) -> Point {
+        let (a, d) = (FieldElement::from(A), FieldElement::from(D));
+        let tau = d * self.x * other.x * self.y * other.y;
+        let one = FieldElement::from(1);
+
+        Point {
+            x: (self.x * other.y + self.y * other.x) * (one + tau).inverse().unwrap(),
+            y: (self.y * other.y - a * self.x * other.x) * (one - tau).inverse().unwrap(),
+        }
+    }
+
+    pub fn double(&self) -> Point {
+        self.add(self)
+    }
+
+    /// Multiplies by the cofactor 8, mapping every point into the prime order subgroup.
+    pub fn clear_cofactor(&self) -> Point {
+        self.double().double().double()
+    }
+
+    /// Multiplies by the integer whose little-endian bits are given.
+    pub fn mul_bits(&self, bits: &[bool]) -> Point {
+        bits.iter().rev().fold(Point::identity(), |acc, &bit| {
+            let doubled = acc.double();
+            if bit {
+                doubled.add(self)
+            } else {
+                doubled
+            }
+        })
+    }
+
+    pub fn mul(&self, scalar: &Scalar) -> Point {
+        self.mul_bits(&scalar.into_bigint().to_bits_le())
+    }
+
+    /// Multiplies by a field element taken as an integer in `[0, p)`.
+    pub fn mul_field(&self, value: &FieldElement) -> Point {
+        self.mul_bits(&to_bits_le(value, FieldElement::MODULUS_BIT_SIZE as usize))
+    }
+}
+
+/// Reduces a field element modulo the subgroup order.
+pub fn to_scalar(value: &FieldElement) -> Scalar {
+    Scalar::from_le_bytes_mod_order(&value.into_bigint().to_bytes_le())
+}
+
+/// Embeds a scalar in the circuit's field, which is larger than the subgroup order.
+pub fn to_field(scalar: &Scalar) -> FieldElement {
+    FieldElement::from_bigint(scalar.into_bigint()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_points() {
+        assert!(Point::generator().is_on_curve());
+        assert!(Point::base8().is_on_curve());
+        assert_eq!(
+            Point::generator().mul_bits(&[false, false, false, true]),
+            Point::base8()
+        );
+
+        let order = Scalar::MODULUS.to_bits_le();
+        assert_eq!(Point::base8().mul_bits(&order), Point::identity());
+    }
+
+    #[test]
+    fn test_scalar_multiplication_is_linear() {
+        let (x, y) = (Scalar::from(123456789u64), Scalar::from(987654321u64));
+        let base = Point::base8();
+
+        assert_eq!(base.mul(&x).add(&base.mul(&y)), base.mul(&(x + y)));
+        assert_eq!(base.mul(&x).mul(&y), base.mul(&(x * y)));
+        assert!(base.mul(&x).is_on_curve());
+    }
+}
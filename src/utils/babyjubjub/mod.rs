@@ -0,0 +1 @@
+pub mod babyjubjub;
\ No newline at end of file
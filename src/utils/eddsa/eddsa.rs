@@ -0,0 +1,154 @@
+use crate::utils::babyjubjub::babyjubjub::{to_field, to_scalar, Point, Scalar};
+use crate::utils::field::field::FieldElement;
+use crate::utils::poseidon::poseidon::poseidon_hash;
+use ark_ff::UniformRand;
+use rand::Rng;
+
+/// EdDSA signature over Baby Jubjub with Poseidon as the challenge hash, in the scheme of
+/// circomlib's `EdDSAPoseidonVerifier`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub r8: Point,
+    pub s: Scalar,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrivateKey {
+    scalar: Scalar,
+}
+
+impl PrivateKey {
+    pub fn new(scalar: Scalar) -> Self {
+        PrivateKey { scalar }
+    }
+
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        PrivateKey::new(Scalar::rand(rng))
+    }
+
+    pub fn public_key(&self) -> Point {
+        Point::base8().mul(&self.scalar)
+    }
+
+    /// Signs a field element with `S = r + 8 * h * key`, as circomlibjs does with its pruned
+    /// key `8 * key`. The nonce is derived deterministically from the key and message, which is
+    /// enough for generating test witnesses.
+    pub fn sign(&self, message: FieldElement) -> Signature {
+        let nonce = to_scalar(&poseidon_hash(&[to_field(&self.scalar), message]));
+        let r8 = Point::base8().mul(&nonce);
+        let challenge = challenge(&r8, &self.public_key(), message);
+
+        Signature {
+            r8,
+            s: nonce + to_scalar(&challenge) * Scalar::from(8) * self.scalar,
+        }
+    }
+}
+
+/// `Poseidon(R8.x, R8.y, A.x, A.y, message)`.
+pub fn challenge(r8: &Point, public_key: &Point, message: FieldElement) -> FieldElement {
+    poseidon_hash(&[r8.x, r8.y, public_key.x, public_key.y, message])
+}
+
+/// Checks circomlib's equation `S * B8 = R8 + h * (8 * A)`, rejecting public keys of small
+/// order, whose `8 * A` is the identity.
+pub fn verify(public_key: &Point, message: FieldElement, signature: &Signature) -> bool {
+    if !public_key.is_on_curve() || !signature.r8.is_on_curve() {
+        return false;
+    }
+    let a8 = public_key.clear_cofactor();
+    if a8 == Point::identity() {
+        return false;
+    }
+
+    let challenge = challenge(&signature.r8, public_key, message);
+    let left = Point::base8().mul(&signature.s);
+    let right = signature.r8.add(&a8.mul_field(&challenge));
+    left == right
+}
+
+/// Signature that passes the verification equation under the public key `(0, -1)` of order
+/// two, whose `8 * A` is the identity: `R8 = S * B8` works for any challenge.
+#[cfg(test)]
+pub(crate) fn small_order_forgery() -> (Point, FieldElement, Signature) {
+    let public_key = Point {
+        x: FieldElement::from(0),
+        y: -FieldElement::from(1),
+    };
+    let s = Scalar::from(1);
+    let signature = Signature {
+        r8: Point::base8().mul(&s),
+        s,
+    };
+    (public_key, FieldElement::from(1234), signature)
+}
+
+/// Signature of the bytes 0 to 9 under the private key `000102...0001` from the tests of
+/// circomlibjs' `signPoseidon`.
+#[cfg(test)]
+pub(crate) fn circomlibjs_signature() -> (Point, FieldElement, Signature) {
+    use std::str::FromStr;
+
+    let field = |value: &str| FieldElement::from_str(value).unwrap();
+    let public_key = Point {
+        x: field("13277427435165878497778222415993513565335242147425444199013288855685581939618"),
+        y: field("13622229784656158136036771217484571176836296686641868549125388198837476602820"),
+    };
+    let signature = Signature {
+        r8: Point {
+            x: field(
+                "11384336176656855268977457483345535180380036354188103142384839473266348197733",
+            ),
+            y: field(
+                "15383486972088797283337779941324724402501462225528836549661220478783371668959",
+            ),
+        },
+        s: Scalar::from_str(
+            "1672775540645840396591609181675628451599263765380031905495115170613215233181",
+        )
+        .unwrap(),
+    };
+    // The message bytes read as a little-endian integer.
+    let message = field("42649378395939397566720");
+    (public_key, message, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = PrivateKey::random(&mut rand::thread_rng());
+        let public_key = key.public_key();
+        let message = FieldElement::from(1234);
+        let signature = key.sign(message);
+
+        assert!(verify(&public_key, message, &signature));
+        assert!(!verify(
+            &public_key,
+            message + FieldElement::from(1),
+            &signature
+        ));
+
+        let other_key = PrivateKey::random(&mut rand::thread_rng()).public_key();
+        assert!(!verify(&other_key, message, &signature));
+    }
+
+    #[test]
+    fn test_verifies_circomlibjs_signature() {
+        let (public_key, message, signature) = circomlibjs_signature();
+        assert!(verify(&public_key, message, &signature));
+        assert!(!verify(
+            &public_key,
+            message + FieldElement::from(1),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_rejects_small_order_public_key() {
+        let (public_key, message, signature) = small_order_forgery();
+        assert!(!verify(&public_key, message, &signature));
+    }
+}
@@ -0,0 +1 @@
+pub mod eddsa;
\ No newline at end of file
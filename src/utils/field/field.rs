@@ -0,0 +1,92 @@
+use ark_ff::{BigInteger, Field, PrimeField};
+use num_bigint::{BigInt, BigUint, Sign};
+
+/// Scalar field of BN254, the field every circuit is compiled over.
+pub type FieldElement = ark_bn254::Fr;
+
+/// Bit length of the field modulus.
+pub const MODULUS_BITS: usize = FieldElement::MODULUS_BIT_SIZE as usize;
+
+/// Returns the `n` least significant bits of `value`, little-endian.
+pub fn to_bits_le(value: &FieldElement, n: usize) -> Vec<bool> {
+    let bits = value.into_bigint().to_bits_le();
+    (0..n)
+        .map(|i| bits.get(i).copied().unwrap_or(false))
+        .collect()
+}
+
+/// Number of bits needed to represent `value` as an integer in `[0, p)`.
+pub fn bit_length(value: &FieldElement) -> usize {
+    value.into_bigint().num_bits() as usize
+}
+
+/// Recomposes a little-endian bit vector into a field element.
+pub fn from_bits_le(bits: &[bool]) -> FieldElement {
+    let mut value = FieldElement::from(0u64);
+    for &bit in bits.iter().rev() {
+        value.double_in_place();
+        if bit {
+            value += FieldElement::from(1u64);
+        }
+    }
+    value
+}
+
+/// Returns `2^exponent` in the field.
+pub fn power_of_two(exponent: usize) -> FieldElement {
+    let mut value = FieldElement::from(1u64);
+    for _ in 0..exponent {
+        value.double_in_place();
+    }
+    value
+}
+
+/// Reads a field element as an integer in `(-p/2, p/2]`, field negation standing for
+/// integer negation.
+pub fn to_signed(value: &FieldElement) -> BigInt {
+    let magnitude = BigUint::from(*value);
+    let negated = BigUint::from(-*value);
+    if negated < magnitude {
+        -BigInt::from(negated)
+    } else {
+        BigInt::from(magnitude)
+    }
+}
+
+/// Embeds a signed integer, reducing it modulo `p`.
+pub fn from_signed(value: &BigInt) -> FieldElement {
+    let magnitude = FieldElement::from(value.magnitude().clone());
+    if value.sign() == Sign::Minus {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_round_trip() {
+        let value = FieldElement::from(0b1011_0110u64);
+        let bits = to_bits_le(&value, 8);
+
+        assert_eq!(
+            bits,
+            vec![false, true, true, false, true, true, false, true]
+        );
+        assert_eq!(from_bits_le(&bits), value);
+        assert_eq!(bit_length(&value), 8);
+        assert_eq!(bit_length(&-FieldElement::from(1u64)), MODULUS_BITS);
+    }
+
+    #[test]
+    fn test_signed_round_trip() {
+        for value in [-5, 0, 7] {
+            let value = BigInt::from(value);
+            assert_eq!(to_signed(&from_signed(&value)), value);
+        }
+        assert_eq!(to_signed(&-FieldElement::from(3u64)), BigInt::from(-3));
+    }
+}
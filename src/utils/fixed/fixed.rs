@@ -0,0 +1,138 @@
+use crate::circuits::CircuitError;
+use crate::utils::field::field::{from_signed, to_signed, FieldElement};
+use num_bigint::{BigInt, Sign};
+use serde::{Deserialize, Serialize};
+
+/// Bit width of the signed integers fixed-point values are encoded as. Fixed-point gadgets
+/// assume their operands lie in `[-2^(FIXED_POINT_BITS - 1), 2^(FIXED_POINT_BITS - 1))`.
+pub const FIXED_POINT_BITS: usize = 128;
+
+/// Decimal literal such as `{"fixed": "3.1415", "scale": 16}`, encoded in circuits as the
+/// integer `floor(value * 2^scale)`. Literals are validated when constructed or deserialized.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "Literal")]
+pub struct FixedPoint {
+    fixed: String,
+    scale: usize,
+}
+
+#[derive(Deserialize)]
+struct Literal {
+    fixed: String,
+    scale: usize,
+}
+
+impl TryFrom<Literal> for FixedPoint {
+    type Error = CircuitError;
+
+    fn try_from(literal: Literal) -> Result<Self, CircuitError> {
+        FixedPoint::new(&literal.fixed, literal.scale)
+    }
+}
+
+impl FixedPoint {
+    pub fn new(fixed: &str, scale: usize) -> Result<Self, CircuitError> {
+        encode(fixed, scale)?;
+        Ok(FixedPoint {
+            fixed: fixed.to_string(),
+            scale,
+        })
+    }
+
+    pub fn encode(&self) -> FieldElement {
+        encode(&self.fixed, self.scale).expect("Fixed-point literals are validated")
+    }
+}
+
+/// Encodes a decimal literal as `floor(value * 2^scale)`, failing on malformed literals or
+/// values that do not fit in `FIXED_POINT_BITS`.
+pub fn encode(literal: &str, scale: usize) -> Result<FieldElement, CircuitError> {
+    let invalid =
+        || CircuitError::DeserializationError(format!("invalid fixed-point literal {:?}", literal));
+
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, literal),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let numerator: BigInt = format!("{}{}", integer, fraction)
+        .parse()
+        .map_err(|_| invalid())?;
+    let denominator = BigInt::from(10).pow(fraction.len() as u32);
+    let scaled = numerator << scale;
+    let (quotient, remainder) = (&scaled / &denominator, &scaled % &denominator);
+
+    // Rounds towards negative infinity, like the truncation of fixed-point products.
+    let encoded = match (negative, remainder == BigInt::from(0)) {
+        (false, _) => quotient,
+        (true, true) => -quotient,
+        (true, false) => -quotient - 1,
+    };
+    if encoded.bits() >= FIXED_POINT_BITS as u64 {
+        return Err(CircuitError::DeserializationError(format!(
+            "fixed-point literal {:?} does not fit in {} bits",
+            literal, FIXED_POINT_BITS
+        )));
+    }
+    Ok(from_signed(&encoded))
+}
+
+/// Exact decimal expansion of an encoded value, which has at most `scale` fractional digits.
+pub fn decode(value: &FieldElement, scale: usize) -> String {
+    let value = to_signed(value);
+    let sign = if value.sign() == Sign::Minus { "-" } else { "" };
+    let magnitude = BigInt::from(value.magnitude().clone());
+    let integer = &magnitude >> scale;
+    let fraction = &magnitude - (&integer << scale);
+
+    // fraction / 2^scale = fraction * 5^scale / 10^scale
+    let digits = (fraction * BigInt::from(5).pow(scale as u32)).to_string();
+    let digits = format!("{:0>width$}", digits, width = scale);
+    match digits.trim_end_matches('0') {
+        "" => format!("{}{}", sign, integer),
+        digits => format!("{}{}.{}", sign, integer, digits),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_rounds_down() {
+        let encode = |literal, scale| encode(literal, scale).unwrap();
+        assert_eq!(encode("3.1415", 16), FieldElement::from(205881));
+        assert_eq!(encode("-1.5", 4), -FieldElement::from(24));
+        assert_eq!(encode("-0.3", 4), -FieldElement::from(5));
+        assert_eq!(encode("7", 0), FieldElement::from(7));
+        assert_eq!(encode(".5", 1), FieldElement::from(1));
+    }
+
+    #[test]
+    fn test_decoding_is_exact() {
+        let encode = |literal, scale| encode(literal, scale).unwrap();
+        assert_eq!(decode(&encode("3.1415", 16), 16), "3.1414947509765625");
+        assert_eq!(decode(&encode("-1.5", 4), 4), "-1.5");
+        assert_eq!(decode(&encode("12", 8), 8), "12");
+    }
+
+    #[test]
+    fn test_rejects_malformed_literals() {
+        for (literal, scale) in [("1.2.3", 8), ("", 8), ("1e5", 0), ("1", 128)] {
+            assert!(matches!(
+                encode(literal, scale),
+                Err(CircuitError::DeserializationError(_))
+            ));
+        }
+        assert!(serde_json::from_str::<FixedPoint>(r#"{"fixed": "1.2.3", "scale": 8}"#).is_err());
+    }
+}
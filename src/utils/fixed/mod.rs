@@ -0,0 +1 @@
+pub mod fixed;
\ No newline at end of file
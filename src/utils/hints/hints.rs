@@ -0,0 +1,163 @@
+use crate::circuits::CircuitError;
+use crate::utils::field::field::{to_bits_le, FieldElement, MODULUS_BITS};
+use ark_ff::{Field, Zero};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+
+/// Native function computing advice values from the values of its operands, failing with an
+/// `EvaluationError` on operands it is not defined for.
+pub type Hint = fn(&[FieldElement]) -> Result<Vec<FieldElement>, CircuitError>;
+
+/// Hints available to witness generation, by name.
+#[derive(Debug, Clone)]
+pub struct Hints {
+    hints: HashMap<String, Hint>,
+}
+
+impl Default for Hints {
+    /// The built-in hints: `inverse`, `sqrt`, `bits` and `divmod`.
+    fn default() -> Self {
+        let mut hints = Hints {
+            hints: HashMap::new(),
+        };
+        hints.register("inverse", inverse);
+        hints.register("sqrt", sqrt);
+        hints.register("bits", bits);
+        hints.register("divmod", divmod);
+        hints
+    }
+}
+
+impl Hints {
+    /// Registers `hint` under `name`, replacing any hint already registered with that name.
+    pub fn register(&mut self, name: &str, hint: Hint) {
+        self.hints.insert(name.to_string(), hint);
+    }
+
+    /// The hint registered as `name`, failing with an `EvaluationError` when there is none.
+    pub fn get(&self, name: &str) -> Result<Hint, CircuitError> {
+        self.hints
+            .get(name)
+            .copied()
+            .ok_or_else(|| evaluation_error(format!("Unknown hint {}", name)))
+    }
+
+    pub fn compute(
+        &self,
+        name: &str,
+        inputs: &[FieldElement],
+    ) -> Result<Vec<FieldElement>, CircuitError> {
+        self.get(name)?(inputs)
+    }
+}
+
+fn evaluation_error(message: String) -> CircuitError {
+    CircuitError::EvaluationError(message)
+}
+
+fn single(name: &str, inputs: &[FieldElement]) -> Result<FieldElement, CircuitError> {
+    match inputs {
+        [x] => Ok(*x),
+        _ => Err(evaluation_error(format!(
+            "Hint {} expects 1 operand, found {}",
+            name,
+            inputs.len()
+        ))),
+    }
+}
+
+/// Inverse of the operand, 0 for 0.
+pub fn inverse(inputs: &[FieldElement]) -> Result<Vec<FieldElement>, CircuitError> {
+    let x = single("inverse", inputs)?;
+    Ok(vec![x.inverse().unwrap_or_default()])
+}
+
+/// A square root of the operand.
+pub fn sqrt(inputs: &[FieldElement]) -> Result<Vec<FieldElement>, CircuitError> {
+    let x = single("sqrt", inputs)?;
+    let root = x
+        .sqrt()
+        .ok_or_else(|| evaluation_error(format!("{} is not a square", x)))?;
+    Ok(vec![root])
+}
+
+/// Little-endian bits of the operand, `MODULUS_BITS` of them.
+pub fn bits(inputs: &[FieldElement]) -> Result<Vec<FieldElement>, CircuitError> {
+    let x = single("bits", inputs)?;
+    Ok(to_bits_le(&x, MODULUS_BITS)
+        .into_iter()
+        .map(FieldElement::from)
+        .collect())
+}
+
+/// Quotient and remainder of the integer division of the first operand by the second.
+pub fn divmod(inputs: &[FieldElement]) -> Result<Vec<FieldElement>, CircuitError> {
+    let [a, b] = inputs else {
+        return Err(evaluation_error(format!(
+            "Hint divmod expects 2 operands, found {}",
+            inputs.len()
+        )));
+    };
+    if b.is_zero() {
+        return Err(evaluation_error(format!("Division of {} by 0", a)));
+    }
+    let (a, b) = (BigUint::from(*a), BigUint::from(*b));
+    Ok(vec![
+        FieldElement::from(&a / &b),
+        FieldElement::from(&a % &b),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_hints() {
+        let hints = Hints::default();
+        let compute = |name, inputs: &[FieldElement]| hints.compute(name, inputs).unwrap();
+        let x = FieldElement::from(7);
+
+        assert_eq!(compute("inverse", &[x])[0] * x, FieldElement::from(1));
+        assert_eq!(
+            compute("inverse", &[FieldElement::from(0)]),
+            [FieldElement::from(0)]
+        );
+
+        let root = compute("sqrt", &[FieldElement::from(49)])[0];
+        assert_eq!(root * root, FieldElement::from(49));
+
+        let bits = compute("bits", &[FieldElement::from(6)]);
+        assert_eq!(bits.len(), MODULUS_BITS);
+        assert_eq!(bits[..4], [0, 1, 1, 0].map(FieldElement::from));
+
+        let divmod = compute("divmod", &[FieldElement::from(23), x]);
+        assert_eq!(divmod, [3, 2].map(FieldElement::from));
+    }
+
+    #[test]
+    fn test_undefined_hints_fail() {
+        let hints = Hints::default();
+        let zero = FieldElement::from(0);
+        for (name, inputs) in [
+            ("sqrt", vec![FieldElement::from(5)]),
+            ("divmod", vec![FieldElement::from(1), zero]),
+            ("bits", vec![zero, zero]),
+        ] {
+            assert!(matches!(
+                hints.compute(name, &inputs),
+                Err(CircuitError::EvaluationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_hint() {
+        match Hints::default().compute("cube_root", &[FieldElement::from(8)]) {
+            Err(CircuitError::EvaluationError(message)) => {
+                assert_eq!(message, "Unknown hint cube_root")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod hints;
\ No newline at end of file
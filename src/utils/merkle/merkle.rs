@@ -0,0 +1,181 @@
+use crate::utils::field::field::FieldElement;
+use crate::utils::poseidon::poseidon::poseidon_hash;
+use std::collections::HashMap;
+
+/// Two-to-one compression function used to hash tree nodes.
+pub trait MerkleHasher {
+    fn hash(&self, left: FieldElement, right: FieldElement) -> FieldElement;
+}
+
+impl<F: Fn(FieldElement, FieldElement) -> FieldElement> MerkleHasher for F {
+    fn hash(&self, left: FieldElement, right: FieldElement) -> FieldElement {
+        self(left, right)
+    }
+}
+
+/// Poseidon with two inputs, the hash matching the `Poseidon` circuit operation.
+pub fn poseidon_hasher(left: FieldElement, right: FieldElement) -> FieldElement {
+    poseidon_hash(&[left, right])
+}
+
+/// Authentication path of a leaf, ordered from the leaf level up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleProof {
+    pub leaf: FieldElement,
+    pub path: Vec<FieldElement>,
+    /// `true` when the node at that level is a right child, i.e. its sibling is on the left.
+    pub directions: Vec<bool>,
+}
+
+impl MerkleProof {
+    pub fn compute_root<H: MerkleHasher>(&self, hasher: &H) -> FieldElement {
+        self.path
+            .iter()
+            .zip(&self.directions)
+            .fold(self.leaf, |node, (&sibling, &is_right)| {
+                if is_right {
+                    hasher.hash(sibling, node)
+                } else {
+                    hasher.hash(node, sibling)
+                }
+            })
+    }
+
+    pub fn verify<H: MerkleHasher>(&self, root: FieldElement, hasher: &H) -> bool {
+        self.compute_root(hasher) == root
+    }
+}
+
+/// Binary Merkle tree of fixed depth where unset leaves are zero. Only the nodes above set
+/// leaves are stored, empty subtrees are represented by their precomputed roots.
+pub struct SparseMerkleTree<H: MerkleHasher> {
+    depth: usize,
+    hasher: H,
+    /// Non-empty nodes keyed by `(level, index)`, level 0 being the leaves.
+    nodes: HashMap<(usize, u64), FieldElement>,
+    /// Root of an empty subtree of each height.
+    empty_roots: Vec<FieldElement>,
+}
+
+impl<H: MerkleHasher> SparseMerkleTree<H> {
+    pub fn new(depth: usize, hasher: H) -> Self {
+        assert!(
+            depth <= 64,
+            "Merkle tree depth must be at most 64, found {}",
+            depth
+        );
+
+        let mut empty_roots = vec![FieldElement::from(0)];
+        for level in 0..depth {
+            let below = empty_roots[level];
+            empty_roots.push(hasher.hash(below, below));
+        }
+
+        SparseMerkleTree {
+            depth,
+            hasher,
+            nodes: HashMap::new(),
+            empty_roots,
+        }
+    }
+
+    /// Builds a tree holding `leaves` at indices `0..leaves.len()`.
+    pub fn from_leaves(depth: usize, hasher: H, leaves: &[FieldElement]) -> Self {
+        let mut tree = SparseMerkleTree::new(depth, hasher);
+        for (index, &leaf) in leaves.iter().enumerate() {
+            tree.insert(index as u64, leaf);
+        }
+        tree
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn node(&self, level: usize, index: u64) -> FieldElement {
+        self.nodes
+            .get(&(level, index))
+            .copied()
+            .unwrap_or(self.empty_roots[level])
+    }
+
+    pub fn leaf(&self, index: u64) -> FieldElement {
+        self.node(0, index)
+    }
+
+    pub fn root(&self) -> FieldElement {
+        self.node(self.depth, 0)
+    }
+
+    /// Sets a leaf and recomputes the nodes on its path to the root.
+    pub fn insert(&mut self, index: u64, leaf: FieldElement) {
+        assert!(
+            self.depth == 64 || index >> self.depth == 0,
+            "Leaf index {} out of range for depth {}",
+            index,
+            self.depth
+        );
+
+        self.nodes.insert((0, index), leaf);
+        let mut index = index;
+        for level in 0..self.depth {
+            let (left, right) = (self.node(level, index & !1), self.node(level, index | 1));
+            index >>= 1;
+            let parent = self.hasher.hash(left, right);
+            self.nodes.insert((level + 1, index), parent);
+        }
+    }
+
+    pub fn proof(&self, index: u64) -> MerkleProof {
+        let mut path = Vec::with_capacity(self.depth);
+        let mut directions = Vec::with_capacity(self.depth);
+        let mut position = index;
+        for level in 0..self.depth {
+            path.push(self.node(level, position ^ 1));
+            directions.push(position & 1 == 1);
+            position >>= 1;
+        }
+
+        MerkleProof {
+            leaf: self.leaf(index),
+            path,
+            directions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proofs_verify_against_root() {
+        let leaves: Vec<FieldElement> = (1..=5).map(FieldElement::from).collect();
+        let tree = SparseMerkleTree::from_leaves(3, poseidon_hasher, &leaves);
+
+        for index in 0..8 {
+            let proof = tree.proof(index);
+            assert!(proof.verify(tree.root(), &poseidon_hasher));
+        }
+
+        let mut forged = tree.proof(2);
+        forged.leaf = FieldElement::from(42);
+        assert!(!forged.verify(tree.root(), &poseidon_hasher));
+    }
+
+    #[test]
+    fn test_sparse_tree_matches_dense_computation() {
+        let add = |left: FieldElement, right: FieldElement| left + right + left * right;
+        let mut tree = SparseMerkleTree::new(2, add);
+        assert_eq!(
+            tree.root(),
+            add(add(0.into(), 0.into()), add(0.into(), 0.into()))
+        );
+
+        tree.insert(3, FieldElement::from(7));
+        tree.insert(0, FieldElement::from(2));
+        let expected = add(add(2.into(), 0.into()), add(0.into(), 7.into()));
+        assert_eq!(tree.root(), expected);
+        assert_eq!(tree.proof(3).directions, vec![true, true]);
+    }
+}
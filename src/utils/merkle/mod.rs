@@ -0,0 +1 @@
+pub mod merkle;
\ No newline at end of file
@@ -0,0 +1,126 @@
+use crate::utils::field::field::FieldElement;
+use ark_ff::{Field, PrimeField};
+use sha3::{Digest, Keccak256};
+
+/// Round count of circomlib's `MiMC7`.
+pub const MIMC7_ROUNDS: usize = 91;
+/// Round count of circomlib's `MiMCSponge` Feistel permutation.
+pub const FEISTEL_ROUNDS: usize = 220;
+
+/// Round constants of circomlib's `MiMC7`: `c_0 = 0`, the following ones are a keccak256 chain
+/// seeded with `"mimc"`, read big-endian and reduced into the field.
+pub fn round_constants(rounds: usize) -> Vec<FieldElement> {
+    keccak_chain(b"mimc", rounds)
+}
+
+/// Round constants of circomlib's `MiMCSponge`: a keccak256 chain seeded with `"mimcsponge"`,
+/// with both the first and the last constant set to zero.
+pub fn feistel_constants(rounds: usize) -> Vec<FieldElement> {
+    let mut constants = keccak_chain(b"mimcsponge", rounds);
+    if let Some(last) = constants.last_mut() {
+        *last = FieldElement::from(0);
+    }
+    constants
+}
+
+fn keccak_chain(seed: &[u8], rounds: usize) -> Vec<FieldElement> {
+    let mut constants = Vec::with_capacity(rounds);
+    let mut digest = Keccak256::digest(seed);
+    for round in 0..rounds {
+        if round == 0 {
+            constants.push(FieldElement::from(0));
+        } else {
+            digest = Keccak256::digest(digest);
+            constants.push(FieldElement::from_be_bytes_mod_order(&digest));
+        }
+    }
+    constants
+}
+
+/// MiMC-7 block cipher: `rounds` applications of `x -> (x + k + c_i)^7`, followed by a final
+/// key addition.
+pub fn mimc7(x: FieldElement, key: FieldElement, rounds: usize) -> FieldElement {
+    let mut state = x;
+    for constant in round_constants(rounds) {
+        state = (state + key + constant).pow([7]);
+    }
+    state + key
+}
+
+/// MiMC Feistel permutation with the x^5 round function. The halves are swapped after every
+/// round but the last.
+pub fn mimc_feistel(
+    left: FieldElement,
+    right: FieldElement,
+    key: FieldElement,
+    rounds: usize,
+) -> (FieldElement, FieldElement) {
+    let (mut left, mut right) = (left, right);
+    for (round, constant) in feistel_constants(rounds).into_iter().enumerate() {
+        let mixed = right + (left + key + constant).pow([5]);
+        if round + 1 < rounds {
+            right = left;
+            left = mixed;
+        } else {
+            right = mixed;
+        }
+    }
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_single_round_is_seventh_power() {
+        let (x, key) = (FieldElement::from(3), FieldElement::from(5));
+        assert_eq!(mimc7(x, key, 1), FieldElement::from(8u64.pow(7) + 5));
+        assert_eq!(mimc7(x, key, 0), FieldElement::from(8));
+    }
+
+    #[test]
+    fn test_matches_circomlib_vectors() {
+        let constant = |digits| FieldElement::from_str(digits).unwrap();
+        assert_eq!(
+            round_constants(MIMC7_ROUNDS)[1],
+            constant(
+                "20888961410941983456478427210666206549300505294776164667214940546594746570981"
+            )
+        );
+        let feistel = feistel_constants(FEISTEL_ROUNDS);
+        assert_eq!(
+            feistel[1],
+            constant(
+                "7120861356467848435263064379192047478074060781135320967663101236819528304084"
+            )
+        );
+        assert_eq!(feistel[FEISTEL_ROUNDS - 1], FieldElement::from(0));
+
+        let (x, key) = (FieldElement::from(1), FieldElement::from(2));
+        assert_eq!(
+            mimc7(x, key, MIMC7_ROUNDS),
+            constant(
+                "10594780656576967754230020536574539122676596303354946869887184401991294982664"
+            )
+        );
+    }
+
+    #[test]
+    fn test_feistel_rounds_are_invertible() {
+        let key = FieldElement::from(11);
+        let (left, right) = (FieldElement::from(1), FieldElement::from(2));
+        let rounds = 10;
+        let (mut out_left, mut out_right) = mimc_feistel(left, right, key, rounds);
+
+        // Undo the rounds in reverse order.
+        for (round, constant) in feistel_constants(rounds).into_iter().enumerate().rev() {
+            if round + 1 < rounds {
+                (out_left, out_right) = (out_right, out_left);
+            }
+            out_right -= (out_left + key + constant).pow([5]);
+        }
+        assert_eq!((out_left, out_right), (left, right));
+    }
+}
@@ -0,0 +1 @@
+pub mod mimc;
\ No newline at end of file
@@ -1 +1,10 @@
-pub mod polynomial;
\ No newline at end of file
+pub mod babyjubjub;
+pub mod eddsa;
+pub mod field;
+pub mod fixed;
+pub mod hints;
+pub mod merkle;
+pub mod mimc;
+pub mod polynomial;
+pub mod poseidon;
+pub mod secp256k1;
\ No newline at end of file
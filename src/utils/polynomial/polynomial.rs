@@ -67,7 +67,7 @@ impl Add for Polynomial {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        let (mut shorter, mut longer) = if self.coefficients.len() < other.coefficients.len() {
+        let (shorter, longer) = if self.coefficients.len() < other.coefficients.len() {
             (self.coefficients.clone(), other.coefficients)
         } else {
             (other.coefficients.clone(), self.coefficients)
@@ -0,0 +1,83 @@
+use crate::utils::field::field::{FieldElement, MODULUS_BITS};
+use ark_ff::{BigInteger, PrimeField};
+
+/// Grain LFSR in self-shrinking mode, seeded as in the Poseidon reference parameter script.
+pub struct GrainLfsr {
+    state: u128,
+}
+
+impl GrainLfsr {
+    const STATE_BITS: u32 = 80;
+
+    pub fn new(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // field = 1 (prime field), sbox = 0 (x^alpha), field size, t, R_F, R_P, then 30 ones.
+        let fields: [(u128, u32); 7] = [
+            (1, 2),
+            (0, 4),
+            (MODULUS_BITS as u128, 12),
+            (width as u128, 12),
+            (full_rounds as u128, 10),
+            (partial_rounds as u128, 10),
+            ((1 << 30) - 1, 30),
+        ];
+
+        // Bit i of the state is the i-th bit of the initial sequence.
+        let mut state = 0u128;
+        let mut position = 0;
+        for (value, length) in fields {
+            for i in (0..length).rev() {
+                state |= ((value >> i) & 1) << position;
+                position += 1;
+            }
+        }
+
+        let mut lfsr = GrainLfsr { state };
+        for _ in 0..160 {
+            lfsr.clock();
+        }
+        lfsr
+    }
+
+    fn clock(&mut self) -> bool {
+        let s = self.state;
+        let bit = ((s >> 62) ^ (s >> 51) ^ (s >> 38) ^ (s >> 23) ^ (s >> 13) ^ s) & 1;
+        self.state = (s >> 1) | (bit << (Self::STATE_BITS - 1));
+        bit == 1
+    }
+
+    /// Output bits are taken in pairs: the second bit is kept only when the first one is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// Next `MODULUS_BITS` bits as a big-endian integer.
+    fn next_bits(&mut self) -> Vec<bool> {
+        let mut bits: Vec<bool> = (0..MODULUS_BITS).map(|_| self.next_bit()).collect();
+        bits.reverse();
+        bits
+    }
+
+    /// Samples a field element, rejecting integers not below the modulus.
+    pub fn next_field_element(&mut self) -> FieldElement {
+        loop {
+            let bits = self.next_bits();
+            let integer = <FieldElement as PrimeField>::BigInt::from_bits_le(&bits);
+            if let Some(element) = FieldElement::from_bigint(integer) {
+                return element;
+            }
+        }
+    }
+
+    /// Samples a field element, reducing the integer modulo the field.
+    pub fn next_field_element_mod_order(&mut self) -> FieldElement {
+        let bits = self.next_bits();
+        let bytes = <FieldElement as PrimeField>::BigInt::from_bits_le(&bits).to_bytes_le();
+        FieldElement::from_le_bytes_mod_order(&bytes)
+    }
+}
@@ -0,0 +1,2 @@
+mod grain;
+pub mod poseidon;
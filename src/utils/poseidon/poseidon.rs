@@ -0,0 +1,174 @@
+use super::grain::GrainLfsr;
+use crate::utils::field::field::FieldElement;
+use ark_ff::Field;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+pub const FULL_ROUNDS: usize = 8;
+pub const MIN_WIDTH: usize = 2;
+pub const MAX_WIDTH: usize = 17;
+
+/// Partial rounds for widths 2 to 17, matching the circomlib instantiation over BN254.
+const PARTIAL_ROUNDS: [usize; MAX_WIDTH - MIN_WIDTH + 1] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+/// Poseidon with the x^5 S-box over the circuit's field.
+#[derive(Debug, Clone)]
+pub struct PoseidonParameters {
+    pub width: usize,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    /// `width` constants per round, added before the S-box layer.
+    pub round_constants: Vec<FieldElement>,
+    pub mds: Vec<Vec<FieldElement>>,
+}
+
+impl PoseidonParameters {
+    /// Generates round constants and a Cauchy MDS matrix with the Grain LFSR, as the reference
+    /// parameter script does.
+    pub fn generate(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut grain = GrainLfsr::new(width, full_rounds, partial_rounds);
+
+        let round_constants = (0..(full_rounds + partial_rounds) * width)
+            .map(|_| grain.next_field_element())
+            .collect();
+
+        let mds = loop {
+            let samples: Vec<FieldElement> = (0..2 * width)
+                .map(|_| grain.next_field_element_mod_order())
+                .collect();
+            if samples.iter().collect::<HashSet<_>>().len() != samples.len() {
+                continue;
+            }
+
+            let (xs, ys) = samples.split_at(width);
+            let entries: Option<Vec<Vec<FieldElement>>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x + y).inverse()).collect())
+                .collect();
+            if let Some(mds) = entries {
+                break mds;
+            }
+        };
+
+        PoseidonParameters {
+            width,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+
+    pub fn is_full_round(&self, round: usize) -> bool {
+        round < self.full_rounds / 2 || round >= self.full_rounds / 2 + self.partial_rounds
+    }
+}
+
+/// Parameters for the given state width, generated on first use.
+pub fn parameters(width: usize) -> &'static PoseidonParameters {
+    static PARAMETERS: [OnceLock<PoseidonParameters>; MAX_WIDTH + 1] =
+        [const { OnceLock::new() }; MAX_WIDTH + 1];
+
+    assert!(
+        (MIN_WIDTH..=MAX_WIDTH).contains(&width),
+        "Poseidon width must be between {} and {}, found {}",
+        MIN_WIDTH,
+        MAX_WIDTH,
+        width
+    );
+    PARAMETERS[width].get_or_init(|| {
+        PoseidonParameters::generate(width, FULL_ROUNDS, PARTIAL_ROUNDS[width - MIN_WIDTH])
+    })
+}
+
+/// Applies the Poseidon permutation to `state` in place.
+pub fn permute(state: &mut [FieldElement]) {
+    let parameters = parameters(state.len());
+    let width = parameters.width;
+
+    for round in 0..parameters.rounds() {
+        for (element, constant) in state
+            .iter_mut()
+            .zip(&parameters.round_constants[round * width..])
+        {
+            *element += constant;
+        }
+
+        let sbox_count = if parameters.is_full_round(round) {
+            width
+        } else {
+            1
+        };
+        for element in &mut state[..sbox_count] {
+            *element = element.pow([5]);
+        }
+
+        let mixed: Vec<FieldElement> = parameters
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+            .collect();
+        state.copy_from_slice(&mixed);
+    }
+}
+
+/// Hashes 1 to 16 field elements, compatible with circomlib's `Poseidon(n)`.
+pub fn poseidon_hash(inputs: &[FieldElement]) -> FieldElement {
+    let mut state = vec![FieldElement::from(0)];
+    state.extend_from_slice(inputs);
+    permute(&mut state);
+    state[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::PrimeField;
+    use std::str::FromStr;
+
+    fn from_hex(hex: &str) -> FieldElement {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        FieldElement::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_permutation_matches_reference_vectors() {
+        // poseidonperm_x5_254_3 from the Poseidon reference implementation.
+        let mut state = [0, 1, 2].map(FieldElement::from);
+        permute(&mut state);
+        assert_eq!(
+            state,
+            [
+                from_hex("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"),
+                from_hex("0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29"),
+                from_hex("0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c"),
+            ]
+        );
+
+        // poseidonperm_x5_254_5
+        let mut state = [0, 1, 2, 3, 4].map(FieldElement::from);
+        permute(&mut state);
+        assert_eq!(
+            state[0],
+            from_hex("299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465")
+        );
+    }
+
+    #[test]
+    fn test_hash_matches_circomlib() {
+        let expected = FieldElement::from_str(
+            "7853200120776062878684798364095072458815029376092732009249414926327459813530",
+        )
+        .unwrap();
+        assert_eq!(poseidon_hash(&[1, 2].map(FieldElement::from)), expected);
+    }
+}
@@ -0,0 +1 @@
+pub mod secp256k1;
\ No newline at end of file
@@ -0,0 +1,96 @@
+use crate::utils::field::field::FieldElement;
+use k256::ecdsa::{Signature, VerifyingKey};
+use k256::elliptic_curve::point::DecompressPoint;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, FieldBytes, ProjectivePoint};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// Width of the limbs 256-bit integers are split into inside circuits.
+pub const LIMB_BITS: usize = 64;
+/// Number of limbs of a 256-bit integer.
+pub const LIMBS: usize = 4;
+
+/// Order of secp256k1's base field.
+pub fn base_modulus() -> BigUint {
+    BigUint::from_str(
+        "115792089237316195423570985008687907853269984665640564039457584007908834671663",
+    )
+    .unwrap()
+}
+
+/// Order of the secp256k1 group, the modulus of its scalar field.
+pub fn group_order() -> BigUint {
+    BigUint::from_str(
+        "115792089237316195423570985008687907852837564279074904382605163141518161494337",
+    )
+    .unwrap()
+}
+
+/// Affine coordinates of a point other than the identity.
+pub fn coordinates(point: &AffinePoint) -> (BigUint, BigUint) {
+    let encoded = point.to_encoded_point(false);
+    (
+        BigUint::from_bytes_be(encoded.x().expect("Point at infinity")),
+        BigUint::from_bytes_be(encoded.y().expect("Point at infinity")),
+    )
+}
+
+/// A point nobody knows the discrete logarithm of, found by hashing `tag` with a counter until
+/// the digest is the x-coordinate of a curve point.
+pub fn nothing_up_my_sleeve_point(tag: &str) -> ProjectivePoint {
+    (0u64..)
+        .find_map(|counter| {
+            let digest = Sha256::new()
+                .chain_update(tag)
+                .chain_update(counter.to_be_bytes())
+                .finalize();
+            let x = FieldBytes::clone_from_slice(&digest);
+            let point: Option<AffinePoint> = AffinePoint::decompress(&x, 0.into()).into();
+            point.map(ProjectivePoint::from)
+        })
+        .unwrap()
+}
+
+/// Splits a 256-bit integer into little-endian limbs.
+pub fn to_limbs(value: &BigUint) -> Vec<FieldElement> {
+    let mut digits = value.to_u64_digits();
+    digits.resize(LIMBS, 0);
+    digits.into_iter().map(FieldElement::from).collect()
+}
+
+/// Operands of an ECDSA verification: the limbs of the prehashed message, of the signature's
+/// `r` and `s`, and of the public key's coordinates.
+pub fn ecdsa_operands(
+    public_key: &VerifyingKey,
+    prehash: &[u8; 32],
+    signature: &Signature,
+) -> Vec<FieldElement> {
+    let (x, y) = coordinates(public_key.as_affine());
+    let (r, s) = signature.split_bytes();
+
+    [
+        BigUint::from_bytes_be(prehash),
+        BigUint::from_bytes_be(&r),
+        BigUint::from_bytes_be(&s),
+        x,
+        y,
+    ]
+    .iter()
+    .flat_map(to_limbs)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nothing_up_my_sleeve_point_is_on_curve() {
+        let point = nothing_up_my_sleeve_point("test").to_affine();
+        let (x, y) = coordinates(&point);
+        let p = base_modulus();
+        assert_eq!(y.modpow(&2u32.into(), &p), (x.pow(3) + 7u32) % &p);
+    }
+}
@@ -0,0 +1,202 @@
+use ark_bn254::Bn254;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_snark::SNARK;
+use rand::{CryptoRng, RngCore};
+
+use crate::circuits::CircuitError;
+use crate::r1cs::r1cs::{LinearCombination, R1CS};
+use crate::utils::field::field::FieldElement;
+
+pub type Proof = ark_groth16::Proof<Bn254>;
+pub type ProvingKey = ark_groth16::ProvingKey<Bn254>;
+pub type VerifyingKey = ark_groth16::VerifyingKey<Bn254>;
+
+type Groth16 = ark_groth16::Groth16<Bn254>;
+
+/// Generates Groth16 keys for the constraint system, whose public inputs are the values of
+/// `R1CS::public_variables`: `out`, then the circuit's public inputs.
+pub fn setup<R: RngCore + CryptoRng>(r1cs: &R1CS, rng: &mut R) -> Result<ProvingKey, CircuitError> {
+    let (proving_key, _) = Groth16::circuit_specific_setup(
+        Synthesizer {
+            r1cs,
+            witness: None,
+        },
+        rng,
+    )
+    .map_err(proof_error)?;
+    Ok(proving_key)
+}
+
+/// Proves knowledge of a witness vector ordered as the variable map, which must satisfy the
+/// constraints.
+pub fn prove<R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey,
+    r1cs: &R1CS,
+    witness: &[FieldElement],
+    rng: &mut R,
+) -> Result<Proof, CircuitError> {
+    if witness.len() != r1cs.variable_map.len() || !r1cs.is_satisfied_by_vector(witness) {
+        return Err(CircuitError::ProofError(
+            "witness does not satisfy the constraints".to_string(),
+        ));
+    }
+    Groth16::prove(
+        proving_key,
+        Synthesizer {
+            r1cs,
+            witness: Some(witness),
+        },
+        rng,
+    )
+    .map_err(proof_error)
+}
+
+pub fn verify(
+    verifying_key: &VerifyingKey,
+    public_inputs: &[FieldElement],
+    proof: &Proof,
+) -> Result<bool, CircuitError> {
+    if public_inputs.len() + 1 != verifying_key.gamma_abc_g1.len() {
+        return Err(CircuitError::ProofError(format!(
+            "expected {} public inputs, found {}",
+            verifying_key.gamma_abc_g1.len() - 1,
+            public_inputs.len()
+        )));
+    }
+    Groth16::verify(verifying_key, public_inputs, proof).map_err(proof_error)
+}
+
+/// Values of the public variables in a witness vector, in the order `verify` takes them.
+pub fn public_signals(r1cs: &R1CS, witness: &[FieldElement]) -> Vec<FieldElement> {
+    r1cs.public_variables()
+        .iter()
+        .map(|&index| witness[index])
+        .collect()
+}
+
+fn proof_error(error: SynthesisError) -> CircuitError {
+    CircuitError::ProofError(error.to_string())
+}
+
+/// Constraint system handed to arkworks, with the witness when proving.
+struct Synthesizer<'a> {
+    r1cs: &'a R1CS,
+    witness: Option<&'a [FieldElement]>,
+}
+
+impl ConstraintSynthesizer<FieldElement> for Synthesizer<'_> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<FieldElement>,
+    ) -> Result<(), SynthesisError> {
+        let value = |wire: usize| {
+            self.witness
+                .map(|witness| witness[wire])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        // Public variables are allocated first, in the order verifiers take them.
+        let public = self.r1cs.public_variables();
+        let mut variables = vec![Variable::One; self.r1cs.variable_map.len()];
+        for &wire in &public {
+            variables[wire] = cs.new_input_variable(|| value(wire))?;
+        }
+        for (wire, variable) in variables.iter_mut().enumerate().skip(1) {
+            if !public.contains(&wire) {
+                *variable = cs.new_witness_variable(|| value(wire))?;
+            }
+        }
+
+        let terms = |row: &LinearCombination| {
+            ark_relations::r1cs::LinearCombination(
+                row.iter()
+                    .map(|&(wire, coeff)| (coeff, variables[wire]))
+                    .collect(),
+            )
+        };
+        let (a, b, c) = self.r1cs.get_constraint_matrices();
+        for ((a, b), c) in a.iter().zip(b).zip(c) {
+            cs.enforce_constraint(terms(a), terms(b), terms(c))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::Circuit;
+    use crate::zk_proofs::snarkjs::{public_inputs_from_json, public_inputs_to_json};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_proof_verifies_with_public_signals_only() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Multiply",
+                "operands": [{"operation": "Add", "operands": ["x", "y"]}, "y"],
+                "public_inputs": ["y"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(3)),
+            ("y".to_string(), FieldElement::from(4)),
+        ]);
+        let witness = r1cs.compute_witness_vector(&circuit, inputs).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let proving_key = setup(&r1cs, &mut rng).unwrap();
+        let proof = prove(&proving_key, &r1cs, &witness, &mut rng).unwrap();
+        let public = public_signals(&r1cs, &witness);
+        assert_eq!(public, [28, 4].map(FieldElement::from));
+        assert!(verify(&proving_key.vk, &public, &proof).unwrap());
+        for other in [[29, 4], [28, 5]] {
+            assert!(!verify(&proving_key.vk, &other.map(FieldElement::from), &proof).unwrap());
+        }
+
+        let mut tampered = witness.clone();
+        tampered[2] += FieldElement::from(1);
+        assert!(matches!(
+            prove(&proving_key, &r1cs, &tampered, &mut rng),
+            Err(CircuitError::ProofError(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_verifies_against_exported_public_signals() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Add",
+                "operands": [{"operation": "Multiply", "operands": ["x", "y"]}, "z"],
+                "public_inputs": ["z", "y"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(3)),
+            ("y".to_string(), FieldElement::from(4)),
+            ("z".to_string(), FieldElement::from(5)),
+        ]);
+        let witness = r1cs.compute_witness(&circuit, inputs).unwrap();
+        let vector = r1cs.witness_vector(&witness).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let proving_key = setup(&r1cs, &mut rng).unwrap();
+        let proof = prove(&proving_key, &r1cs, &vector, &mut rng).unwrap();
+
+        let (mut r1cs_file, mut wtns_file) = (Vec::new(), Vec::new());
+        r1cs.write_r1cs(&mut r1cs_file).unwrap();
+        r1cs.write_wtns(&witness, &mut wtns_file).unwrap();
+        let exported = R1CS::read_r1cs(r1cs_file.as_slice()).unwrap();
+        let wires = exported.read_wtns(wtns_file.as_slice()).unwrap();
+
+        let public = public_signals(&exported, &wires);
+        assert_eq!(public, [17, 5, 4].map(FieldElement::from));
+        assert_eq!(public, public_signals(&r1cs, &vector));
+        let imported = public_inputs_from_json(&public_inputs_to_json(&public)).unwrap();
+        assert!(verify(&proving_key.vk, &imported, &proof).unwrap());
+    }
+}
@@ -1 +1,4 @@
-pub mod qap;
\ No newline at end of file
+pub mod groth16;
+pub mod qap;
+pub mod snarkjs;
+pub mod solidity;
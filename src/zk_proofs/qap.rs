@@ -1,11 +1,13 @@
-use crate::{circuits::IndexedMap, Polynomial};
+use crate::{circuits::IndexedMap, FieldElement, Polynomial};
 use std::collections::HashMap;
 
+// The reduction from R1CS is not implemented yet, so nothing reads the fields.
+#[allow(dead_code)]
 pub struct QAP {
     a_polynomials: Vec<Polynomial>,
     b_polynomials: Vec<Polynomial>,
     c_polynomials: Vec<Polynomial>,
-    witness: HashMap<String, i64>,
+    witness: HashMap<String, FieldElement>,
     variable_map: IndexedMap<String>,
 }
 
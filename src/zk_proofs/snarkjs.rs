@@ -0,0 +1,309 @@
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::circuits::CircuitError;
+use crate::utils::field::field::FieldElement;
+use crate::zk_proofs::groth16::{Proof, VerifyingKey};
+
+const PROTOCOL: &str = "groth16";
+/// Name of BN254 in snarkjs.
+const CURVE: &str = "bn128";
+
+/// Projective coordinates of a G1 point, `z` being 1 or 0 for the point at infinity.
+type G1Json = [String; 3];
+/// Projective coordinates of a G2 point, each as the coefficients `[c0, c1]` of `Fq2`.
+type G2Json = [[String; 2]; 3];
+
+#[derive(Serialize, Deserialize)]
+struct ProofJson {
+    pi_a: G1Json,
+    pi_b: G2Json,
+    pi_c: G1Json,
+    protocol: String,
+    curve: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VerificationKeyJson {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    vk_alpha_1: G1Json,
+    vk_beta_2: G2Json,
+    vk_gamma_2: G2Json,
+    vk_delta_2: G2Json,
+    /// The pairing of alpha and beta, which snarkjs precomputes.
+    vk_alphabeta_12: [[[String; 2]; 3]; 2],
+    #[serde(rename = "IC")]
+    ic: Vec<G1Json>,
+}
+
+/// Serializes a proof as the `proof.json` of snarkjs.
+pub fn proof_to_json(proof: &Proof) -> String {
+    to_json(&ProofJson {
+        pi_a: g1_to_json(&proof.a),
+        pi_b: g2_to_json(&proof.b),
+        pi_c: g1_to_json(&proof.c),
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+    })
+}
+
+pub fn proof_from_json(json: &str) -> Result<Proof, CircuitError> {
+    let proof: ProofJson = from_json(json)?;
+    check_scheme(&proof.protocol, &proof.curve)?;
+    Ok(Proof {
+        a: g1_from_json(&proof.pi_a)?,
+        b: g2_from_json(&proof.pi_b)?,
+        c: g1_from_json(&proof.pi_c)?,
+    })
+}
+
+/// Serializes a verifying key as the `verification_key.json` of snarkjs.
+pub fn verifying_key_to_json(verifying_key: &VerifyingKey) -> String {
+    let alphabeta = Bn254::pairing(verifying_key.alpha_g1, verifying_key.beta_g2).0;
+    let fq6_to_json = |c: &ark_bn254::Fq6| [&c.c0, &c.c1, &c.c2].map(fq2_to_json);
+
+    to_json(&VerificationKeyJson {
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+        n_public: verifying_key.gamma_abc_g1.len() - 1,
+        vk_alpha_1: g1_to_json(&verifying_key.alpha_g1),
+        vk_beta_2: g2_to_json(&verifying_key.beta_g2),
+        vk_gamma_2: g2_to_json(&verifying_key.gamma_g2),
+        vk_delta_2: g2_to_json(&verifying_key.delta_g2),
+        vk_alphabeta_12: [fq6_to_json(&alphabeta.c0), fq6_to_json(&alphabeta.c1)],
+        ic: verifying_key.gamma_abc_g1.iter().map(g1_to_json).collect(),
+    })
+}
+
+/// Deserializes a verifying key from the `verification_key.json` of snarkjs. The precomputed
+/// `vk_alphabeta_12` is not read.
+pub fn verifying_key_from_json(json: &str) -> Result<VerifyingKey, CircuitError> {
+    let key: VerificationKeyJson = from_json(json)?;
+    check_scheme(&key.protocol, &key.curve)?;
+    if key.ic.len() != key.n_public + 1 {
+        return Err(CircuitError::DeserializationError(format!(
+            "expected {} IC points, found {}",
+            key.n_public + 1,
+            key.ic.len()
+        )));
+    }
+    Ok(VerifyingKey {
+        alpha_g1: g1_from_json(&key.vk_alpha_1)?,
+        beta_g2: g2_from_json(&key.vk_beta_2)?,
+        gamma_g2: g2_from_json(&key.vk_gamma_2)?,
+        delta_g2: g2_from_json(&key.vk_delta_2)?,
+        gamma_abc_g1: key.ic.iter().map(g1_from_json).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Serializes public inputs as the `public.json` of snarkjs.
+pub fn public_inputs_to_json(public_inputs: &[FieldElement]) -> String {
+    to_json(&public_inputs.iter().map(decimal).collect::<Vec<_>>())
+}
+
+pub fn public_inputs_from_json(json: &str) -> Result<Vec<FieldElement>, CircuitError> {
+    let values: Vec<String> = from_json(json)?;
+    values.iter().map(|value| parse(value)).collect()
+}
+
+/// Formats JSON as snarkjs does, indenting by one space.
+fn to_json<T: Serialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+    value.serialize(&mut serializer).unwrap();
+    String::from_utf8(bytes).unwrap()
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, CircuitError> {
+    serde_json::from_str(json).map_err(|e| CircuitError::DeserializationError(e.to_string()))
+}
+
+fn check_scheme(protocol: &str, curve: &str) -> Result<(), CircuitError> {
+    if protocol != PROTOCOL || curve != CURVE {
+        return Err(CircuitError::DeserializationError(format!(
+            "expected a {} proof over {}, found {} over {}",
+            PROTOCOL, CURVE, protocol, curve
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn decimal<F: PrimeField>(value: &F) -> String {
+    let integer: BigUint = value.into_bigint().into();
+    integer.to_string()
+}
+
+/// Parses the decimal representation of a field element, rejecting non-canonical values.
+fn parse<F: PrimeField>(value: &str) -> Result<F, CircuitError> {
+    value
+        .parse::<BigUint>()
+        .ok()
+        .and_then(|integer| F::BigInt::try_from(integer).ok())
+        .and_then(F::from_bigint)
+        .ok_or_else(|| {
+            CircuitError::DeserializationError(format!("invalid field element {:?}", value))
+        })
+}
+
+fn fq2_to_json(value: &Fq2) -> [String; 2] {
+    [decimal(&value.c0), decimal(&value.c1)]
+}
+
+fn fq2_from_json(value: &[String; 2]) -> Result<Fq2, CircuitError> {
+    Ok(Fq2::new(parse(&value[0])?, parse(&value[1])?))
+}
+
+fn g1_to_json(point: &G1Affine) -> G1Json {
+    if point.infinity {
+        return ["0", "1", "0"].map(String::from);
+    }
+    [decimal(&point.x), decimal(&point.y), "1".to_string()]
+}
+
+fn g1_from_json(point: &G1Json) -> Result<G1Affine, CircuitError> {
+    let z: Fq = parse(&point[2])?;
+    if z == Fq::from(0) {
+        return Ok(G1Affine::identity());
+    }
+    check_affine(z == Fq::from(1))?;
+    check_point(G1Affine::new_unchecked(
+        parse(&point[0])?,
+        parse(&point[1])?,
+    ))
+}
+
+fn g2_to_json(point: &G2Affine) -> G2Json {
+    if point.infinity {
+        return [["0", "0"], ["1", "0"], ["0", "0"]].map(|c| c.map(String::from));
+    }
+    [
+        fq2_to_json(&point.x),
+        fq2_to_json(&point.y),
+        ["1", "0"].map(String::from),
+    ]
+}
+
+fn g2_from_json(point: &G2Json) -> Result<G2Affine, CircuitError> {
+    let z = fq2_from_json(&point[2])?;
+    if z == Fq2::from(0) {
+        return Ok(G2Affine::identity());
+    }
+    check_affine(z == Fq2::from(1))?;
+    check_point(G2Affine::new_unchecked(
+        fq2_from_json(&point[0])?,
+        fq2_from_json(&point[1])?,
+    ))
+}
+
+/// snarkjs normalizes points, so that `z` is 1 unless the point is at infinity.
+fn check_affine(normalized: bool) -> Result<(), CircuitError> {
+    if !normalized {
+        return Err(CircuitError::DeserializationError(
+            "expected normalized coordinates".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_point<P: ark_ec::short_weierstrass::SWCurveConfig>(
+    point: ark_ec::short_weierstrass::Affine<P>,
+) -> Result<ark_ec::short_weierstrass::Affine<P>, CircuitError> {
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(CircuitError::DeserializationError(format!(
+            "point {} is not in the group",
+            point
+        )));
+    }
+    Ok(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::Circuit;
+    use crate::r1cs::r1cs::R1CS;
+    use crate::zk_proofs::groth16::{prove, public_signals, setup, verify};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    fn proof() -> (VerifyingKey, Proof, Vec<FieldElement>) {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": {"Pow": {"exponent": 3}},
+                "operands": [{"operation": "Add", "operands": ["x", "y"]}],
+                "public_inputs": ["x", "y"]}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(2)),
+            ("y".to_string(), -FieldElement::from(7)),
+        ]);
+        let witness = r1cs.compute_witness_vector(&circuit, inputs).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let proving_key = setup(&r1cs, &mut rng).unwrap();
+        let proof = prove(&proving_key, &r1cs, &witness, &mut rng).unwrap();
+        (proving_key.vk, proof, public_signals(&r1cs, &witness))
+    }
+
+    #[test]
+    fn test_snarkjs_files_round_trip() {
+        let (verifying_key, proof, public) = proof();
+
+        let proof_json = proof_to_json(&proof);
+        let key_json = verifying_key_to_json(&verifying_key);
+        let public_json = public_inputs_to_json(&public);
+        assert!(proof_json.starts_with("{\n \"pi_a\": [\n  \""));
+        assert!(proof_json.ends_with("\"protocol\": \"groth16\",\n \"curve\": \"bn128\"\n}"));
+        assert!(key_json.contains("\"nPublic\": 3,"));
+        let [out, minus_seven] = [-125, -7].map(|x| decimal(&FieldElement::from(x)));
+        assert_eq!(
+            public_json,
+            format!("[\n \"{}\",\n \"2\",\n \"{}\"\n]", out, minus_seven)
+        );
+
+        let imported_proof = proof_from_json(&proof_json).unwrap();
+        let imported_key = verifying_key_from_json(&key_json).unwrap();
+        let imported_public = public_inputs_from_json(&public_json).unwrap();
+        assert_eq!(imported_proof, proof);
+        assert_eq!(imported_key, verifying_key);
+        assert_eq!(imported_public, public);
+        assert!(verify(&imported_key, &imported_public, &imported_proof).unwrap());
+    }
+
+    #[test]
+    fn test_snarkjs_files_are_validated() {
+        let (verifying_key, proof, _) = proof();
+        let proof_json = proof_to_json(&proof);
+        let key_json = verifying_key_to_json(&verifying_key);
+        let x = decimal(&proof.a.x);
+        let modulus = BigUint::from(FieldElement::MODULUS).to_string();
+
+        for json in [
+            proof_json.replace("bn128", "bls12381"),
+            proof_json.replacen(&x, &(BigUint::from(proof.a.x) + 1u32).to_string(), 1),
+        ] {
+            assert!(matches!(
+                proof_from_json(&json),
+                Err(CircuitError::DeserializationError(_))
+            ));
+        }
+        assert!(matches!(
+            verifying_key_from_json(&key_json.replace("\"nPublic\": 3", "\"nPublic\": 2")),
+            Err(CircuitError::DeserializationError(_))
+        ));
+        assert!(matches!(
+            public_inputs_from_json(&format!("[\"{}\"]", modulus)),
+            Err(CircuitError::DeserializationError(_))
+        ));
+    }
+}
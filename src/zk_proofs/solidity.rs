@@ -0,0 +1,459 @@
+use ark_bn254::{Fq, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::circuits::CircuitError;
+use crate::utils::field::field::FieldElement;
+use crate::zk_proofs::groth16::{Proof, VerifyingKey};
+use crate::zk_proofs::snarkjs::decimal;
+
+/// Verifier contract adapted from the `verifier_groth16.sol.ejs` template of snarkjs, which is
+/// licensed under GPL-3.0, so the generated contracts keep its SPDX header. The precompiles at
+/// 0x06, 0x07 and 0x08 add and multiply G1 points and check the product of pairings.
+const VERIFIER: &str = r#"// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.7.0 <0.9.0;
+
+contract Groth16Verifier {
+    // Scalar field size
+    uint256 constant r = {scalar_modulus};
+    // Base field size
+    uint256 constant q = {base_modulus};
+
+    // Verification Key data
+{constants}
+    // Memory data
+    uint16 constant pVk = 0;
+    uint16 constant pPairing = 128;
+
+    uint16 constant pLastMem = 896;
+
+    function verifyProof(uint[2] calldata _pA, uint[2][2] calldata _pB, uint[2] calldata _pC, uint[{public_inputs}] calldata _pubSignals) public view returns (bool) {
+        assembly {
+            function checkField(v) {
+                if iszero(lt(v, r)) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            // G1 function to multiply a G1 value(x,y) to value in an address
+            function g1_mulAccC(pR, x, y, s) {
+                let success
+                let mIn := mload(0x40)
+                mstore(mIn, x)
+                mstore(add(mIn, 32), y)
+                mstore(add(mIn, 64), s)
+
+                success := staticcall(sub(gas(), 2000), 7, mIn, 96, mIn, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+
+                mstore(add(mIn, 64), mload(pR))
+                mstore(add(mIn, 96), mload(add(pR, 32)))
+
+                success := staticcall(sub(gas(), 2000), 6, mIn, 128, pR, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            function checkPairing(pA, pB, pC, pubSignals, pMem) -> isOk {
+                let _pPairing := add(pMem, pPairing)
+                let _pVk := add(pMem, pVk)
+
+                mstore(_pVk, IC0x)
+                mstore(add(_pVk, 32), IC0y)
+
+                // Compute the linear combination vk_x
+{linear_combination}
+                // -A
+                mstore(_pPairing, calldataload(pA))
+                mstore(add(_pPairing, 32), mod(sub(q, calldataload(add(pA, 32))), q))
+
+                // B
+                mstore(add(_pPairing, 64), calldataload(pB))
+                mstore(add(_pPairing, 96), calldataload(add(pB, 32)))
+                mstore(add(_pPairing, 128), calldataload(add(pB, 64)))
+                mstore(add(_pPairing, 160), calldataload(add(pB, 96)))
+
+                // alpha1
+                mstore(add(_pPairing, 192), alphax)
+                mstore(add(_pPairing, 224), alphay)
+
+                // beta2
+                mstore(add(_pPairing, 256), betax1)
+                mstore(add(_pPairing, 288), betax2)
+                mstore(add(_pPairing, 320), betay1)
+                mstore(add(_pPairing, 352), betay2)
+
+                // vk_x
+                mstore(add(_pPairing, 384), mload(add(pMem, pVk)))
+                mstore(add(_pPairing, 416), mload(add(pMem, add(pVk, 32))))
+
+                // gamma2
+                mstore(add(_pPairing, 448), gammax1)
+                mstore(add(_pPairing, 480), gammax2)
+                mstore(add(_pPairing, 512), gammay1)
+                mstore(add(_pPairing, 544), gammay2)
+
+                // C
+                mstore(add(_pPairing, 576), calldataload(pC))
+                mstore(add(_pPairing, 608), calldataload(add(pC, 32)))
+
+                // delta2
+                mstore(add(_pPairing, 640), deltax1)
+                mstore(add(_pPairing, 672), deltax2)
+                mstore(add(_pPairing, 704), deltay1)
+                mstore(add(_pPairing, 736), deltay2)
+
+                let success := staticcall(sub(gas(), 2000), 8, _pPairing, 768, _pPairing, 0x20)
+
+                isOk := and(success, mload(_pPairing))
+            }
+
+            let pMem := mload(0x40)
+            mstore(0x40, add(pMem, pLastMem))
+
+            // Validate that all evaluations ∈ F
+{field_checks}
+            // Validate all evaluations
+            let isValid := checkPairing(_pA, _pB, _pC, _pubSignals, pMem)
+
+            mstore(0, isValid)
+            return(0, 0x20)
+        }
+    }
+}
+"#;
+
+/// Generates a standalone Solidity contract verifying Groth16 proofs for the verifying key,
+/// through `verifyProof` called with the arguments of `calldata`. Fails with a `ProofError`
+/// when the key has no public inputs, which Solidity cannot pass as a `uint[0]`.
+pub fn verifier_contract(verifying_key: &VerifyingKey) -> Result<String, CircuitError> {
+    let public_inputs = match verifying_key.gamma_abc_g1.len() {
+        0 | 1 => {
+            return Err(CircuitError::ProofError(
+                "the verifier contract needs at least one public input".to_string(),
+            ))
+        }
+        points => points - 1,
+    };
+
+    let mut constants = String::new();
+    let mut constant = |name: &str, value: String| {
+        constants.push_str(&format!("    uint256 constant {} = {};\n", name, value))
+    };
+    let mut g1 = |name: &str, point: &G1Affine| {
+        let [x, y] = g1_coordinates(point);
+        constant(&format!("{}x", name), decimal(&x));
+        constant(&format!("{}y", name), decimal(&y));
+    };
+    g1("alpha", &verifying_key.alpha_g1);
+    for (i, point) in verifying_key.gamma_abc_g1.iter().enumerate() {
+        g1(&format!("IC{}", i), point);
+    }
+    for (name, point) in [
+        ("beta", &verifying_key.beta_g2),
+        ("gamma", &verifying_key.gamma_g2),
+        ("delta", &verifying_key.delta_g2),
+    ] {
+        let [x1, x2, y1, y2] = g2_coordinates(point);
+        constant(&format!("{}x1", name), decimal(&x1));
+        constant(&format!("{}x2", name), decimal(&x2));
+        constant(&format!("{}y1", name), decimal(&y1));
+        constant(&format!("{}y2", name), decimal(&y2));
+    }
+
+    let linear_combination: String = (1..=public_inputs)
+        .map(|i| {
+            format!(
+                "                g1_mulAccC(_pVk, IC{}x, IC{}y, calldataload(add(pubSignals, {})))\n",
+                i,
+                i,
+                (i - 1) * 32
+            )
+        })
+        .collect();
+    let field_checks: String = (0..public_inputs)
+        .map(|i| {
+            format!(
+                "            checkField(calldataload(add(_pubSignals, {})))\n",
+                i * 32
+            )
+        })
+        .collect();
+
+    Ok(VERIFIER
+        .replace("{scalar_modulus}", &modulus::<FieldElement>())
+        .replace("{base_modulus}", &modulus::<Fq>())
+        .replace("{constants}", &constants)
+        .replace("{public_inputs}", &public_inputs.to_string())
+        .replace("{linear_combination}", &linear_combination)
+        .replace("{field_checks}", &field_checks))
+}
+
+/// Formats the arguments of `verifyProof` for a proof and its public inputs, as snarkjs does
+/// for `zkey export soliditycalldata`.
+pub fn calldata(proof: &Proof, public_inputs: &[FieldElement]) -> String {
+    let [a_x, a_y] = g1_coordinates(&proof.a).map(|c| word(&c));
+    let [b_x1, b_x2, b_y1, b_y2] = g2_coordinates(&proof.b).map(|c| word(&c));
+    let [c_x, c_y] = g1_coordinates(&proof.c).map(|c| word(&c));
+    let inputs: Vec<String> = public_inputs.iter().map(word).collect();
+
+    format!(
+        "[{}, {}],[[{}, {}],[{}, {}]],[{}, {}],[{}]",
+        a_x,
+        a_y,
+        b_x1,
+        b_x2,
+        b_y1,
+        b_y2,
+        c_x,
+        c_y,
+        inputs.join(",")
+    )
+}
+
+/// Coordinates of a G1 point as the precompiles take them, `(0, 0)` being the point at
+/// infinity.
+fn g1_coordinates(point: &G1Affine) -> [Fq; 2] {
+    match point.infinity {
+        true => [Fq::from(0); 2],
+        false => [point.x, point.y],
+    }
+}
+
+/// Coordinates of a G2 point as the pairing precompile takes them, with the coefficient of
+/// `u` first in each `Fq2`.
+fn g2_coordinates(point: &G2Affine) -> [Fq; 4] {
+    match point.infinity {
+        true => [Fq::from(0); 4],
+        false => [point.x.c1, point.x.c0, point.y.c1, point.y.c0],
+    }
+}
+
+/// A field element as a quoted 32-byte hexadecimal word.
+fn word<F: PrimeField>(value: &F) -> String {
+    let hex: String = value
+        .into_bigint()
+        .to_bytes_be()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("\"0x{}\"", hex)
+}
+
+fn modulus<F: PrimeField>() -> String {
+    let modulus: num_bigint::BigUint = F::MODULUS.into();
+    modulus.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk_proofs::snarkjs::verifying_key_from_json;
+
+    fn sample_key() -> VerifyingKey {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/zk_proofs/sample_proofs/verification_key.json"
+        );
+        verifying_key_from_json(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_contract_embeds_verifying_key() {
+        let verifying_key = sample_key();
+        let contract = verifier_contract(&verifying_key).unwrap();
+        assert!(contract.starts_with("// SPDX-License-Identifier: GPL-3.0\n"));
+        assert!(contract.contains("uint[1] calldata _pubSignals"));
+
+        let [x, y] = g1_coordinates(&verifying_key.gamma_abc_g1[1]);
+        assert!(contract.contains(&format!("uint256 constant IC1x = {};", decimal(&x))));
+        assert!(contract.contains(&format!("uint256 constant IC1y = {};", decimal(&y))));
+        assert!(contract.contains("g1_mulAccC(_pVk, IC1x, IC1y, calldataload(add(pubSignals, 0)))"));
+        assert!(contract.contains("checkField(calldataload(add(_pubSignals, 0)))"));
+    }
+
+    #[test]
+    fn test_contract_needs_public_inputs() {
+        let mut verifying_key = sample_key();
+        verifying_key.gamma_abc_g1.truncate(1);
+        assert!(matches!(
+            verifier_contract(&verifying_key),
+            Err(CircuitError::ProofError(_))
+        ));
+    }
+}
+
+// The contract is compiled by the `solc` on the PATH, so the tests running it in the EVM only
+// run with the `solc-tests` feature.
+#[cfg(all(test, feature = "solc-tests"))]
+mod contract_tests {
+    use super::*;
+    use crate::circuits::Circuit;
+    use crate::r1cs::r1cs::R1CS;
+    use crate::zk_proofs::groth16::{prove, public_signals, setup, verify};
+    use crate::zk_proofs::snarkjs::{
+        proof_from_json, public_inputs_from_json, verifying_key_from_json,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use revm::db::InMemoryDB;
+    use revm::primitives::{AccountInfo, Address, Bytecode, ExecutionResult, TxKind, U256};
+    use revm::Evm;
+    use sha3::{Digest, Keccak256};
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// Runtime bytecode of the contract, compiled by `solc`.
+    fn compile(contract: &str) -> Vec<u8> {
+        let mut solc = Command::new("solc")
+            .args(["--combined-json", "bin-runtime", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("solc must be on the PATH to run the solc-tests");
+        solc.stdin
+            .take()
+            .unwrap()
+            .write_all(contract.as_bytes())
+            .unwrap();
+        let output = solc.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let (_, compiled) = json["contracts"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .find(|(name, _)| name.ends_with(":Groth16Verifier"))
+            .unwrap();
+        let hex = compiled["bin-runtime"].as_str().unwrap();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Calls `verifyProof` of the deployed bytecode with the arguments of `calldata`,
+    /// returning whether it accepts the proof.
+    fn run_contract(code: &[u8], calldata: &str) -> bool {
+        let words: Vec<U256> = calldata
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| word.starts_with("0x"))
+            .map(|word| word.parse().unwrap())
+            .collect();
+        // The proof takes eight words, the public inputs the others.
+        let signature = format!(
+            "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+            words.len() - 8
+        );
+        let mut input = Keccak256::digest(signature)[..4].to_vec();
+        input.extend(words.iter().flat_map(|word| word.to_be_bytes::<32>()));
+
+        let address = Address::with_last_byte(0x42);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.to_vec().into())),
+                ..Default::default()
+            },
+        );
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Call(address);
+                tx.data = input.into();
+            })
+            .build();
+        match evm.transact().unwrap().result {
+            ExecutionResult::Success { output, .. } => {
+                !U256::from_be_slice(output.data()).is_zero()
+            }
+            result => panic!("verifyProof did not return: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_contract_agrees_with_native_verifier() {
+        let mut circuit = Circuit::from_json(
+            r#"{"operation": "Multiply",
+                "operands": [{"operation": {"ToBits": {"n": 8}}, "operands": ["x"]}, "y"],
+                "public_inputs": ["y", "z"],
+                "types": {"z": "u8"}}"#,
+        )
+        .unwrap();
+        let mut r1cs = R1CS::new(circuit.hash_and_index_circuit());
+        r1cs.generate_r1cs_constraints(&circuit, true);
+        let inputs = HashMap::from([
+            ("x".to_string(), FieldElement::from(200)),
+            ("y".to_string(), -FieldElement::from(1)),
+            ("z".to_string(), FieldElement::from(0)),
+        ]);
+        let witness = r1cs.compute_witness_vector(&circuit, inputs).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let proving_key = setup(&r1cs, &mut rng).unwrap();
+        let proof = prove(&proving_key, &r1cs, &witness, &mut rng).unwrap();
+        let public = public_signals(&r1cs, &witness);
+        let code = compile(&verifier_contract(&proving_key.vk).unwrap());
+
+        let mut other_public = public.clone();
+        other_public[2] = FieldElement::from(1);
+        let other_proof = prove(&proving_key, &r1cs, &witness, &mut rng).unwrap();
+        let mismatched_proof = Proof {
+            c: other_proof.c,
+            ..proof.clone()
+        };
+        for (proof, public) in [
+            (&proof, &public),
+            (&other_proof, &public),
+            (&proof, &other_public),
+            (&mismatched_proof, &public),
+        ] {
+            assert_eq!(
+                run_contract(&code, &calldata(proof, public)),
+                verify(&proving_key.vk, public, proof).unwrap()
+            );
+        }
+        assert!(run_contract(&code, &calldata(&proof, &public)));
+    }
+
+    #[test]
+    fn test_contract_verifies_snarkjs_proof() {
+        // A key, proof and public signals exported by snarkjs for the same zkey, from the test
+        // vectors of risc0-groth16.
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/zk_proofs/sample_proofs/");
+        let read = |name: &str| std::fs::read_to_string(format!("{}{}", dir, name)).unwrap();
+        let verifying_key = verifying_key_from_json(&read("verification_key.json")).unwrap();
+        let proof = proof_from_json(&read("proof.json")).unwrap();
+        let public = public_inputs_from_json(&read("public.json")).unwrap();
+        assert!(verify(&verifying_key, &public, &proof).unwrap());
+
+        let code = compile(&verifier_contract(&verifying_key).unwrap());
+        let arguments = calldata(&proof, &public);
+        assert!(run_contract(&code, &arguments));
+
+        // The public signal plus the scalar modulus passes the pairing check unless rejected.
+        let signal = word(&public[0]);
+        let modulus: num_bigint::BigUint = FieldElement::MODULUS.into();
+        let aliased = num_bigint::BigUint::from(public[0]) + modulus;
+        let aliased = format!("\"0x{:064x}\"", aliased);
+        assert!(!run_contract(&code, &arguments.replace(&signal, &aliased)));
+
+        let other_public = [public[0] + FieldElement::from(1)];
+        assert!(!run_contract(&code, &calldata(&proof, &other_public)));
+    }
+}